@@ -0,0 +1,394 @@
+//! Headless counterpart of the desktop app for nightly jobs and CI. Reads and
+//! writes the same `ArkData.json`, `settings.json`, `skip_rules.json` and
+//! `mod_map.json` files.
+
+use artis_arkdata_manager::diff::{self, DataDiff};
+use artis_arkdata_manager::scrapers::{
+    self, checkpoint::Checkpoint, common::ScraperClient, layout::Baseline, mods::ModMap,
+    progress::StdoutSink, registry::ScraperRegistry, report::ScrapeReport,
+    revisions::RevisionCache, rules::SkipRuleSet, rules::SkipRules, ScrapeInputs,
+};
+use artis_arkdata_manager::types::{settings::Settings, ArkData, Edition};
+use artis_arkdata_manager::validation;
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_FILTER: &str = "warn,artis_arkdata_manager=info";
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+
+#[derive(Parser)]
+#[command(
+    name = "arkdata",
+    version,
+    about = "Scrape, check and compare ArkData files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Scrape the wiki into a data file
+    Scrape(ScrapeArgs),
+    /// Check every entry of a data file; exits non-zero if any is invalid
+    Validate {
+        data: PathBuf,
+        /// Print the issues as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show which entries were added, removed or changed between two data files
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Print the diff as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add entries from a scraped file that the existing file lacks
+    Merge {
+        existing: PathBuf,
+        scraped: PathBuf,
+        /// Where to write the merged data; defaults to the existing file
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Write a data file as pretty-printed JSON, optionally limited to some collections
+    Export {
+        data: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+        /// Collections to keep, e.g. `--only creatures --only items`
+        #[arg(long)]
+        only: Vec<String>,
+        /// Keep only entries that exist in this game, `ase` or `asa`
+        #[arg(long)]
+        edition: Option<Edition>,
+    },
+}
+
+#[derive(Args)]
+struct ScrapeArgs {
+    /// Where to write the scraped data
+    #[arg(long)]
+    out: PathBuf,
+    /// Settings file with the scraper configuration
+    #[arg(long)]
+    settings: Option<PathBuf>,
+    /// Skip rules file; the built-in rules are used without one
+    #[arg(long)]
+    rules: Option<PathBuf>,
+    /// Mod map file; the built-in mappings are used without one
+    #[arg(long)]
+    mods: Option<PathBuf>,
+    /// List of icon paths for the icons stage
+    #[arg(long, default_value = "resources/icons.txt")]
+    icons: PathBuf,
+    /// Record progress here so an interrupted run can be resumed
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+    /// Continue the run recorded in --checkpoint
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+    /// Where to write the scrape report
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Earlier scrape report, or a directory of them, to check the entry
+    /// counts and page layout against; without one the drift check is off
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Cache of engram pages by revision, so unchanged pages are not refetched
+    #[arg(long)]
+    revisions: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_env("ARKDATA_LOG")
+                .unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER)),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let result = match Cli::parse().command {
+        Command::Scrape(args) => scrape(args).await,
+        Command::Validate { data, json } => validate(&data, json),
+        Command::Diff { old, new, json } => compare(&old, &new, json),
+        Command::Merge {
+            existing,
+            scraped,
+            out,
+        } => merge(&existing, &scraped, out.as_deref()),
+        Command::Export {
+            data,
+            out,
+            only,
+            edition,
+        } => export(&data, &out, &only, edition),
+    };
+
+    let code = match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            EXIT_FAILURE
+        }
+    };
+    std::process::exit(code);
+}
+
+async fn scrape(args: ScrapeArgs) -> Result<i32, String> {
+    let settings: Settings = match args.settings {
+        Some(path) => read_json(&path)?,
+        None => Settings::default(),
+    };
+    let rules: SkipRuleSet = match args.rules {
+        Some(path) => read_json(&path)?,
+        None => SkipRuleSet::default(),
+    };
+    let mods: ModMap = match args.mods {
+        Some(path) => read_json(&path)?,
+        None => ModMap::default(),
+    };
+    mods.validate()?;
+
+    let client = ScraperClient::new(&settings.scraper)
+        .map_err(|e| format!("Invalid scraper configuration: {}", e))?;
+    let inputs = ScrapeInputs {
+        checkpoint: match args.checkpoint {
+            Some(path) if args.resume => Checkpoint::resume(path)?,
+            Some(path) => Checkpoint::start(path),
+            None => Checkpoint::disabled(),
+        },
+        rules: SkipRules::compile(&rules)?,
+        mods,
+        baseline: match args.baseline {
+            Some(path) => read_baseline(&path, settings.scraper.min_entry_ratio)?,
+            None => Baseline::new(settings.scraper.min_entry_ratio),
+        },
+        icons_file: if args.icons.exists() {
+            Some(args.icons)
+        } else {
+            None
+        },
+        revisions: match args.revisions {
+            Some(path) => RevisionCache::load(path),
+            None => RevisionCache::disabled(),
+        },
+        edition: settings.scraper.edition,
+        retry: None,
+    };
+
+    let outcome =
+        scrapers::scrape_all(StdoutSink, client, &ScraperRegistry::default(), inputs).await;
+
+    if let Some(path) = args.report {
+        write_json(&path, &outcome.report)?;
+    }
+
+    for stage in &outcome.report.stages {
+        eprintln!(
+            "{:<10} {:>6} entries {:>5} skipped {:>4} failed fetches{}",
+            stage.stage,
+            stage.entries,
+            stage.skipped.len(),
+            stage.failed_fetches.len(),
+            if stage.restored { " (restored)" } else { "" }
+        );
+    }
+
+    if let Some(error) = &outcome.report.error {
+        return Err(format!("Failed to scrape data: {}", error));
+    }
+
+    write_data(&args.out, outcome.data)?;
+    Ok(EXIT_SUCCESS)
+}
+
+/// The most recent successful run of each stage, from a report or a directory
+/// of reports as the app keeps them.
+fn read_baseline(path: &Path, min_ratio: f32) -> Result<Baseline, String> {
+    let mut reports: Vec<ScrapeReport> = Vec::new();
+
+    if path.is_dir() {
+        let entries =
+            fs::read_dir(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        for entry in entries.flatten() {
+            if entry.path().extension().map_or(false, |ext| ext == "json") {
+                reports.push(read_json(&entry.path())?);
+            }
+        }
+        reports.sort_by_key(|report| Reverse(report.started_at));
+    } else {
+        reports.push(read_json(path)?);
+    }
+
+    let mut baseline = Baseline::new(min_ratio);
+    for report in &reports {
+        for stage in report.stages.iter().filter(|s| s.error.is_none()) {
+            baseline.record(&stage.stage, stage.entries, &stage.sections);
+        }
+    }
+
+    Ok(baseline)
+}
+
+fn validate(path: &Path, json: bool) -> Result<i32, String> {
+    let issues = validation::validate_data(&read_data(path)?);
+
+    if json {
+        print_json(&issues)?;
+    } else {
+        for issue in &issues {
+            println!("{}/{}: {}", issue.category, issue.key, issue.error);
+        }
+        eprintln!("{} invalid entries", issues.len());
+    }
+
+    Ok(if issues.is_empty() {
+        EXIT_SUCCESS
+    } else {
+        EXIT_FAILURE
+    })
+}
+
+fn compare(old: &Path, new: &Path, json: bool) -> Result<i32, String> {
+    let changes = diff::diff(&read_data(old)?, &read_data(new)?);
+
+    if json {
+        print_json(&changes)?;
+    } else {
+        print_diff(&changes);
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn print_diff(changes: &DataDiff) {
+    if changes.is_empty() {
+        println!("No differences");
+        return;
+    }
+
+    for (category, collection) in &changes.collections {
+        println!(
+            "{:?}: {} added, {} removed, {} changed",
+            category,
+            collection.added.len(),
+            collection.removed.len(),
+            collection.changed.len()
+        );
+        for key in collection.added.keys() {
+            println!("  + {}", key);
+        }
+        for key in collection.removed.keys() {
+            println!("  - {}", key);
+        }
+        for (key, entry) in &collection.changed {
+            println!("  ~ {} ({})", key, entry.fields.join(", "));
+        }
+    }
+}
+
+fn merge(existing: &Path, scraped: &Path, out: Option<&Path>) -> Result<i32, String> {
+    let mut merged = read_data(existing)?;
+    merged.merge_missing(read_data(scraped)?);
+
+    write_data(out.unwrap_or(existing), merged)?;
+    Ok(EXIT_SUCCESS)
+}
+
+fn export(
+    path: &Path,
+    out: &Path,
+    only: &[String],
+    edition: Option<Edition>,
+) -> Result<i32, String> {
+    let mut data = read_data(path)?;
+
+    if let Some(edition) = edition {
+        data.retain_edition(edition);
+    }
+
+    if !only.is_empty() {
+        let keep = |name: &str| only.iter().any(|o| o.eq_ignore_ascii_case(name));
+        for name in only {
+            if !matches!(
+                name.to_lowercase().as_str(),
+                "creatures" | "items" | "engrams" | "beacons" | "colors" | "icons"
+            ) {
+                return Err(format!("Unknown collection: {}", name));
+            }
+        }
+
+        if !keep("creatures") {
+            data.creatures.clear();
+        }
+        if !keep("items") {
+            data.items.clear();
+        }
+        if !keep("engrams") {
+            data.engrams.clear();
+        }
+        if !keep("beacons") {
+            data.beacons.clear();
+        }
+        if !keep("colors") {
+            data.colors.clear();
+        }
+        if !keep("icons") {
+            data.icons.clear();
+        }
+    }
+
+    write_json(out, &data)?;
+    Ok(EXIT_SUCCESS)
+}
+
+fn read_data(path: &Path) -> Result<ArkData, String> {
+    let mut data: ArkData = read_json(path)?;
+
+    // Same as loading in the app: older files lack a timestamp
+    if data.last_updated == 0 {
+        data.last_updated = Utc::now().timestamp();
+    }
+
+    Ok(data)
+}
+
+fn write_data(path: &Path, mut data: ArkData) -> Result<(), String> {
+    data.last_updated = Utc::now().timestamp();
+    write_json(path, &data)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize data: {}", e))?;
+
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
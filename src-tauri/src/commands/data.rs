@@ -1,16 +1,17 @@
-use crate::types::ArkData;
+use crate::scrapers::keys::{self, KeyMigration};
+use crate::types::{ArkData, Edition};
 use chrono::{Local, Utc};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Runtime};
 
-fn get_app_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+pub(crate) fn get_app_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
     app.path_resolver()
         .resolve_resource("arkdata")
         .expect("failed to get app data directory")
 }
 
-fn ensure_directory(path: &PathBuf) -> std::io::Result<()> {
+pub(crate) fn ensure_directory(path: &PathBuf) -> std::io::Result<()> {
     if !path.exists() {
         fs::create_dir_all(path)?;
     }
@@ -30,9 +31,8 @@ fn get_backups_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
     backups_dir
 }
 
-#[tauri::command]
-pub async fn load_ark_data<R: Runtime>(app: AppHandle<R>) -> Result<ArkData, String> {
-    let data_path = get_data_file_path(&app);
+pub(crate) fn read_ark_data<R: Runtime>(app: &AppHandle<R>) -> Result<ArkData, String> {
+    let data_path = get_data_file_path(app);
 
     if !data_path.exists() {
         return Ok(ArkData::default());
@@ -52,9 +52,11 @@ pub async fn load_ark_data<R: Runtime>(app: AppHandle<R>) -> Result<ArkData, Str
     Ok(ark_data)
 }
 
-#[tauri::command]
-pub async fn save_ark_data<R: Runtime>(app: AppHandle<R>, mut data: ArkData) -> Result<(), String> {
-    let data_path = get_data_file_path(&app);
+pub(crate) fn write_ark_data<R: Runtime>(
+    app: &AppHandle<R>,
+    mut data: ArkData,
+) -> Result<(), String> {
+    let data_path = get_data_file_path(app);
 
     // Update the last_updated timestamp
     data.last_updated = Utc::now().timestamp();
@@ -66,6 +68,40 @@ pub async fn save_ark_data<R: Runtime>(app: AppHandle<R>, mut data: ArkData) ->
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn load_ark_data<R: Runtime>(app: AppHandle<R>) -> Result<ArkData, String> {
+    read_ark_data(&app)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn save_ark_data<R: Runtime>(app: AppHandle<R>, data: ArkData) -> Result<(), String> {
+    write_ark_data(&app, data)
+}
+
+/// Re-keys the stored database under the current key policy, after backing
+/// it up, and returns the migrated data with an old→new key map.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn migrate_keys<R: Runtime>(app: AppHandle<R>) -> Result<KeyMigration, String> {
+    let data = read_ark_data(&app)?;
+    let backup = create_backup(app.clone(), data.clone()).await?;
+
+    let migration = keys::migrate(data);
+    write_ark_data(&app, migration.data.clone())?;
+
+    tracing::info!(
+        backup = backup.as_str(),
+        collections = migration.key_map.len(),
+        collisions = migration.collisions.len(),
+        "Migrated keys"
+    );
+
+    Ok(migration)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn create_backup<R: Runtime>(app: AppHandle<R>, data: ArkData) -> Result<String, String> {
     let backups_dir = get_backups_dir(&app);
 
@@ -83,14 +119,32 @@ pub async fn create_backup<R: Runtime>(app: AppHandle<R>, data: ArkData) -> Resu
 }
 
 #[tauri::command]
-pub async fn export_data(data: ArkData, path: String) -> Result<(), String> {
+#[tracing::instrument(skip(data), err)]
+pub async fn export_data(
+    mut data: ArkData,
+    path: String,
+    edition: Option<Edition>,
+) -> Result<(), String> {
+    if let Some(edition) = edition {
+        data.retain_edition(edition);
+    }
+
     let json = serde_json::to_string_pretty(&data)
         .map_err(|e| format!("Failed to serialize data: {}", e))?;
 
     fs::write(path, json).map_err(|e| format!("Failed to write export file: {}", e))
 }
 
+/// The entries of `data` that exist in `edition`.
+#[tauri::command]
+#[tracing::instrument(skip(data))]
+pub async fn filter_by_edition(mut data: ArkData, edition: Edition) -> ArkData {
+    data.retain_edition(edition);
+    data
+}
+
 #[tauri::command]
+#[tracing::instrument(err)]
 pub async fn import_data(path: String) -> Result<ArkData, String> {
     let data =
         fs::read_to_string(path).map_err(|e| format!("Failed to read import file: {}", e))?;
@@ -0,0 +1,19 @@
+use super::data::{ensure_directory, get_app_dir};
+use crate::logging::{self, LogQuery, LogRecord};
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+pub(crate) fn get_logs_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let logs_dir = get_app_dir(app).join("logs");
+    ensure_directory(&logs_dir).expect("failed to create logs directory");
+    logs_dir
+}
+
+/// Recent log records matching `query`, newest first.
+#[tauri::command]
+pub async fn query_logs<R: Runtime>(
+    app: AppHandle<R>,
+    query: Option<LogQuery>,
+) -> Result<Vec<LogRecord>, String> {
+    logging::query(&get_logs_dir(&app), &query.unwrap_or_default())
+}
@@ -1,7 +1,17 @@
 pub mod data;
+pub mod logs;
+pub mod mods;
+pub mod rules;
+pub mod schedule;
 pub mod scraping;
+pub mod settings;
 pub mod validation;
 
 pub use data::*;
+pub use logs::*;
+pub use mods::*;
+pub use rules::*;
+pub use schedule::*;
 pub use scraping::*;
+pub use settings::*;
 pub use validation::*;
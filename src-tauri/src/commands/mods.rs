@@ -0,0 +1,96 @@
+use super::data::{create_backup, ensure_directory, get_app_dir, read_ark_data, write_ark_data};
+use crate::scrapers::mods::{self, ModMap, ModMigration, UnknownModEntry};
+use crate::types::{ArkData, Mod};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+fn get_mod_map_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("mod_map.json")
+}
+
+fn write_mod_map<R: Runtime>(app: &AppHandle<R>, map: &ModMap) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(map)
+        .map_err(|e| format!("Failed to serialize mod map: {}", e))?;
+
+    fs::write(get_mod_map_path(app), json)
+        .map_err(|e| format!("Failed to write mod map file: {}", e))
+}
+
+/// Reads the mod map file, writing out the defaults on first use so there
+/// is a file to edit. A hand-edited file is checked like a saved one.
+pub(crate) fn read_mod_map<R: Runtime>(app: &AppHandle<R>) -> Result<ModMap, String> {
+    let map_path = get_mod_map_path(app);
+
+    if !map_path.exists() {
+        let map = ModMap::default();
+        write_mod_map(app, &map)?;
+        return Ok(map);
+    }
+
+    let json =
+        fs::read_to_string(&map_path).map_err(|e| format!("Failed to read mod map file: {}", e))?;
+
+    let map: ModMap =
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse mod map: {}", e))?;
+    map.validate()?;
+
+    Ok(map)
+}
+
+#[tauri::command]
+pub async fn load_mod_map<R: Runtime>(app: AppHandle<R>) -> Result<ModMap, String> {
+    read_mod_map(&app)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn save_mod_map<R: Runtime>(app: AppHandle<R>, map: ModMap) -> Result<(), String> {
+    map.validate()?;
+    write_mod_map(&app, &map)
+}
+
+/// Restores the built-in mod map and returns it.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn reset_mod_map<R: Runtime>(app: AppHandle<R>) -> Result<ModMap, String> {
+    let map = ModMap::default();
+    write_mod_map(&app, &map)?;
+    Ok(map)
+}
+
+/// Entries in `data` no mod mapping matched, to see what the table is missing.
+#[tauri::command]
+pub async fn list_unknown_mods(data: ArkData) -> Result<Vec<UnknownModEntry>, String> {
+    Ok(mods::unknown_entries(&data))
+}
+
+/// Replaces the free-text mod names in the stored database with registry
+/// IDs, after backing it up, and returns the migrated data.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn migrate_mods<R: Runtime>(app: AppHandle<R>) -> Result<ModMigration, String> {
+    let data = read_ark_data(&app)?;
+    let backup = create_backup(app.clone(), data.clone()).await?;
+
+    let migration = mods::migrate(data, &read_mod_map(&app)?);
+    write_ark_data(&app, migration.data.clone())?;
+
+    tracing::info!(
+        backup = backup.as_str(),
+        renamed = migration.renamed.len(),
+        registered = migration.registered.len(),
+        "Migrated mods"
+    );
+
+    Ok(migration)
+}
+
+/// The registry entry for mod `id`, for entries added by hand that reference
+/// a mod the database does not list yet.
+#[tauri::command]
+pub async fn mod_registry_entry<R: Runtime>(app: AppHandle<R>, id: String) -> Result<Mod, String> {
+    Ok(read_mod_map(&app)?.registry_entry(&id))
+}
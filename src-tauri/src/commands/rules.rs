@@ -0,0 +1,61 @@
+use super::data::{ensure_directory, get_app_dir};
+use crate::scrapers::rules::{SkipRuleSet, SkipRules};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+fn get_skip_rules_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("skip_rules.json")
+}
+
+fn write_skip_rules<R: Runtime>(app: &AppHandle<R>, rules: &SkipRuleSet) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(rules)
+        .map_err(|e| format!("Failed to serialize skip rules: {}", e))?;
+
+    fs::write(get_skip_rules_path(app), json)
+        .map_err(|e| format!("Failed to write skip rules file: {}", e))
+}
+
+/// Reads the skip rules file, writing out the defaults on first use so there
+/// is a file to edit.
+pub(crate) fn read_skip_rules<R: Runtime>(app: &AppHandle<R>) -> Result<SkipRuleSet, String> {
+    let rules_path = get_skip_rules_path(app);
+
+    if !rules_path.exists() {
+        let rules = SkipRuleSet::default();
+        write_skip_rules(app, &rules)?;
+        return Ok(rules);
+    }
+
+    let json = fs::read_to_string(&rules_path)
+        .map_err(|e| format!("Failed to read skip rules file: {}", e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse skip rules: {}", e))
+}
+
+#[tauri::command]
+pub async fn load_skip_rules<R: Runtime>(app: AppHandle<R>) -> Result<SkipRuleSet, String> {
+    read_skip_rules(&app)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn save_skip_rules<R: Runtime>(
+    app: AppHandle<R>,
+    rules: SkipRuleSet,
+) -> Result<(), String> {
+    // Refuse rules that would fail the next scrape
+    SkipRules::compile(&rules)?;
+    write_skip_rules(&app, &rules)
+}
+
+/// Restores the built-in skip rules and returns them.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn reset_skip_rules<R: Runtime>(app: AppHandle<R>) -> Result<SkipRuleSet, String> {
+    let rules = SkipRuleSet::default();
+    write_skip_rules(&app, &rules)?;
+    Ok(rules)
+}
@@ -0,0 +1,312 @@
+use super::data::{create_backup, ensure_directory, get_app_dir, read_ark_data, write_ark_data};
+use super::scraping::{run_scrape, CheckpointMode, RunKind};
+use super::settings::read_settings;
+use crate::diff::{self, DataDiff};
+use crate::schedule::ScheduleSettings;
+use crate::scrapers::progress::NoopSink;
+use crate::scrapers::report::ScrapeReport;
+use crate::types::ArkData;
+use chrono::{Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// How often the scheduler wakes up to check whether a run is due. Settings
+/// are re-read on every check, so schedule changes apply without a restart.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Wait before trying a failed scheduled scrape again. It doubles with every
+/// failure in a row, up to [`MAX_RETRY_DOUBLINGS`] times, so a wiki that stays
+/// down or a broken configuration doesn't start a full scrape every check.
+const FIRST_RETRY_DELAY_MINUTES: i64 = 15;
+const MAX_RETRY_DOUBLINGS: u32 = 6;
+
+/// Event sent when a scheduled scrape found changes.
+const CHANGES_EVENT: &str = "scheduled-scrape-changes";
+
+/// What a scheduled scrape found, kept until it is applied or discarded.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingChanges {
+    pub created_at: i64,
+    /// What merging the scraped data would change in the saved database.
+    /// Merging only adds entries, so this lists additions.
+    pub changes: DataDiff,
+    /// The scraped data the changes were computed from.
+    pub data: ArkData,
+    pub report: ScrapeReport,
+}
+
+/// Payload of the `scheduled-scrape-changes` event.
+#[derive(Debug, Serialize, Clone)]
+pub struct ChangeSummary {
+    pub created_at: i64,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    /// Whether the changes were already merged because `auto_merge` is on.
+    pub merged: bool,
+}
+
+impl ChangeSummary {
+    fn new(pending: &PendingChanges, merged: bool) -> Self {
+        let collections = pending.changes.collections.values();
+        Self {
+            created_at: pending.created_at,
+            added: collections.clone().map(|c| c.added.len()).sum(),
+            removed: collections.clone().map(|c| c.removed.len()).sum(),
+            changed: collections.map(|c| c.changed.len()).sum(),
+            merged,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SchedulerState {
+    last_run: Option<i64>,
+}
+
+fn get_pending_changes_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("pending_changes.json")
+}
+
+fn get_scheduler_state_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("scheduler_state.json")
+}
+
+fn read_pending_changes<R: Runtime>(app: &AppHandle<R>) -> Result<Option<PendingChanges>, String> {
+    let path = get_pending_changes_path(app);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let json =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read pending changes: {}", e))?;
+
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse pending changes: {}", e))
+}
+
+fn write_pending_changes<R: Runtime>(
+    app: &AppHandle<R>,
+    pending: &PendingChanges,
+) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(pending)
+        .map_err(|e| format!("Failed to serialize pending changes: {}", e))?;
+
+    fs::write(get_pending_changes_path(app), json)
+        .map_err(|e| format!("Failed to write pending changes: {}", e))
+}
+
+fn clear_pending_changes<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let path = get_pending_changes_path(app);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove pending changes: {}", e))?;
+    }
+    Ok(())
+}
+
+fn read_last_run<R: Runtime>(app: &AppHandle<R>) -> Option<i64> {
+    let json = fs::read_to_string(get_scheduler_state_path(app)).ok()?;
+    serde_json::from_str::<SchedulerState>(&json).ok()?.last_run
+}
+
+fn write_last_run<R: Runtime>(app: &AppHandle<R>, last_run: i64) {
+    let state = SchedulerState {
+        last_run: Some(last_run),
+    };
+    let result = serde_json::to_string(&state)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(get_scheduler_state_path(app), json).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Failed to record scheduled scrape time");
+    }
+}
+
+/// Starts the background loop that runs scrapes on the configured schedule.
+/// Without a recorded previous run, the schedule counts from app start. A run
+/// that fails, or can't start because another scrape is running, is tried
+/// again after a delay that grows with every failure in a row.
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_run = read_last_run(&app)
+            .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+            .unwrap_or_else(Local::now);
+        let mut failures = 0;
+        let mut retry_at = None;
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let settings = match read_settings(&app) {
+                Ok(settings) => settings.schedule,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Scheduler could not read settings");
+                    continue;
+                }
+            };
+            if !settings.enabled {
+                continue;
+            }
+
+            let now = Local::now();
+            if !matches!(settings.schedule.next_after(last_run), Some(next) if next <= now) {
+                continue;
+            }
+            if matches!(retry_at, Some(retry_at) if retry_at > now) {
+                continue;
+            }
+
+            match run_scheduled_scrape(&app, &settings).await {
+                Ok(()) => {
+                    last_run = now;
+                    failures = 0;
+                    retry_at = None;
+                    write_last_run(&app, now.timestamp());
+                }
+                Err(e) => {
+                    let delay = retry_delay(failures);
+                    failures += 1;
+                    retry_at = Some(now + delay);
+                    tracing::warn!(
+                        error = %e,
+                        failures,
+                        retry_in_minutes = delay.num_minutes(),
+                        "Scheduled scrape failed"
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// How long to wait after `failures` earlier failures in a row and this one.
+fn retry_delay(failures: u32) -> chrono::Duration {
+    chrono::Duration::minutes(FIRST_RETRY_DELAY_MINUTES << failures.min(MAX_RETRY_DOUBLINGS))
+}
+
+/// Scrapes in the background and records what merging the result would
+/// change in the saved database. Subscribers hear about it only when that is
+/// something new; the database itself is only touched when `auto_merge` is on.
+#[tracing::instrument(skip_all, err)]
+async fn run_scheduled_scrape(app: &AppHandle, settings: &ScheduleSettings) -> Result<(), String> {
+    let outcome = run_scrape(
+        app.clone(),
+        NoopSink,
+        RunKind::Background,
+        CheckpointMode::Off,
+        None,
+    )
+    .await?;
+    let current = read_ark_data(app)?;
+
+    // Compare against the merge rather than the scrape, so changes the merge
+    // would not apply don't come back after every run
+    let mut merged = current.clone();
+    merged.merge_missing(outcome.data.clone());
+    let changes = diff::diff(&current, &merged);
+
+    if changes.is_empty() {
+        tracing::info!("Scheduled scrape found no changes");
+        return clear_pending_changes(app);
+    }
+
+    let previous = read_pending_changes(app).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Ignoring unreadable pending changes");
+        None
+    });
+    let already_reported = matches!(
+        &previous,
+        Some(previous) if same_changes(&previous.changes, &changes)
+    );
+
+    let pending = PendingChanges {
+        created_at: Utc::now().timestamp(),
+        changes,
+        data: outcome.data,
+        report: outcome.report,
+    };
+
+    if settings.auto_merge {
+        let summary = ChangeSummary::new(&pending, true);
+        merge_pending(app, current, pending).await?;
+        emit_changes(app, &summary);
+        return Ok(());
+    }
+
+    write_pending_changes(app, &pending)?;
+    if already_reported {
+        tracing::info!("Scheduled scrape found the same changes as last time");
+    } else {
+        emit_changes(app, &ChangeSummary::new(&pending, false));
+    }
+
+    Ok(())
+}
+
+fn same_changes(a: &DataDiff, b: &DataDiff) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn emit_changes<R: Runtime>(app: &AppHandle<R>, summary: &ChangeSummary) {
+    tracing::info!(
+        added = summary.added,
+        removed = summary.removed,
+        changed = summary.changed,
+        merged = summary.merged,
+        "Scheduled scrape found changes"
+    );
+    app.emit_all(CHANGES_EVENT, summary)
+        .unwrap_or_else(|e| tracing::warn!(error = %e, "Failed to emit scheduled scrape changes"));
+}
+
+/// Backs up `current`, adds the pending entries it lacks and saves the result.
+async fn merge_pending<R: Runtime>(
+    app: &AppHandle<R>,
+    current: ArkData,
+    pending: PendingChanges,
+) -> Result<ArkData, String> {
+    create_backup(app.clone(), current.clone()).await?;
+
+    let mut merged = current;
+    merged.merge_missing(pending.data);
+    write_ark_data(app, merged.clone())?;
+    clear_pending_changes(app)?;
+
+    Ok(merged)
+}
+
+/// The changes found by the last scheduled scrape, if none were applied or
+/// discarded since.
+#[tauri::command]
+pub async fn get_pending_changes<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<Option<PendingChanges>, String> {
+    read_pending_changes(&app)
+}
+
+/// Merges the pending changes into the saved database, after a backup, and
+/// returns the merged data.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn apply_pending_changes<R: Runtime>(app: AppHandle<R>) -> Result<ArkData, String> {
+    let pending = read_pending_changes(&app)?.ok_or("No pending changes to apply")?;
+    let current = read_ark_data(&app)?;
+    merge_pending(&app, current, pending).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn discard_pending_changes<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    clear_pending_changes(&app)
+}
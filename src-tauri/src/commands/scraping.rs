@@ -1,49 +1,352 @@
-use crate::scrapers;
+use super::data::{ensure_directory, get_app_dir, read_ark_data};
+use super::mods::read_mod_map;
+use super::rules::read_skip_rules;
+use super::settings::read_settings;
+use crate::diff::{self, DataDiff};
+use crate::scrapers::{
+    self,
+    checkpoint::Checkpoint,
+    common::ScraperClient,
+    layout::Baseline,
+    progress::{CombinedProgress, ProgressSink},
+    registry::{ScraperInfo, ScraperRegistry},
+    report::{ScrapeReport, ScrapeReportSummary},
+    revisions::RevisionCache,
+    rules::SkipRules,
+    ScrapeInputs, ScrapeOutcome,
+};
 use crate::types::ArkData;
-use std::sync::Arc;
-use tauri::Window;
+use chrono::Local;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime, Window};
 
-#[tauri::command]
-pub async fn start_scraping(window: Window) -> Result<ArkData, String> {
-    // Clone the window for use in a spawned task
-    let window = Arc::new(window);
+/// What a scrape would change in the saved database.
+#[derive(Debug, Serialize, Clone)]
+pub struct ScrapePreview {
+    pub report: ScrapeReport,
+    pub changes: DataDiff,
+}
+
+fn get_checkpoint_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("scrape_checkpoint.json")
+}
+
+fn get_revision_cache_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("engram_revisions.json")
+}
+
+fn get_last_scrape_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("last_scrape.json")
+}
+
+fn read_last_scrape<R: Runtime>(app: &AppHandle<R>) -> Result<ScrapeOutcome, String> {
+    let json = fs::read_to_string(get_last_scrape_path(app))
+        .map_err(|_| "No previous scrape to retry".to_string())?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse last scrape: {}", e))
+}
+
+/// Keeps the output of the latest run, with its failed units, for retries.
+fn write_last_scrape<R: Runtime>(
+    app: &AppHandle<R>,
+    outcome: &ScrapeOutcome,
+) -> Result<(), String> {
+    let json = serde_json::to_string(outcome)
+        .map_err(|e| format!("Failed to serialize last scrape: {}", e))?;
+
+    fs::write(get_last_scrape_path(app), json)
+        .map_err(|e| format!("Failed to write last scrape: {}", e))
+}
+
+fn get_reports_dir<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let reports_dir = get_app_dir(app).join("reports");
+    ensure_directory(&reports_dir).expect("failed to create reports directory");
+    reports_dir
+}
+
+fn save_report<R: Runtime>(app: &AppHandle<R>, report: &ScrapeReport) -> Result<String, String> {
+    // Milliseconds, so runs finishing within the same second keep their own report
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S_%3f");
+    let filename = format!("scrape_report_{}.json", timestamp);
+
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize scrape report: {}", e))?;
+
+    fs::write(get_reports_dir(app).join(&filename), json)
+        .map_err(|e| format!("Failed to write scrape report: {}", e))?;
+
+    Ok(filename)
+}
+
+/// Persisted reports with their ids, newest first. Unreadable files are ignored.
+fn read_reports<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<(String, ScrapeReport)>, String> {
+    let entries = fs::read_dir(get_reports_dir(app))
+        .map_err(|e| format!("Failed to read reports directory: {}", e))?;
+
+    let mut reports = Vec::new();
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().to_string();
+        if !id.ends_with(".json") {
+            continue;
+        }
+
+        let report = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|json| serde_json::from_str::<ScrapeReport>(&json).ok());
+        if let Some(report) = report {
+            reports.push((id, report));
+        }
+    }
+
+    reports.sort_by_key(|(_, report)| Reverse(report.started_at));
+    Ok(reports)
+}
+
+/// The most recent successful run of each stage, from the persisted reports.
+fn previous_baseline<R: Runtime>(app: &AppHandle<R>, min_ratio: f32) -> Result<Baseline, String> {
+    let mut baseline = Baseline::new(min_ratio);
+
+    for (_, report) in read_reports(app)? {
+        for stage in report.stages.iter().filter(|s| s.error.is_none()) {
+            baseline.record(&stage.stage, stage.entries, &stage.sections);
+        }
+    }
+
+    Ok(baseline)
+}
+
+/// Held while a scrape runs, so manual and scheduled runs never overlap.
+#[derive(Default)]
+pub struct ScrapeLock(tokio::sync::Mutex<()>);
+
+/// How a run uses the on-disk checkpoint.
+pub(crate) enum CheckpointMode {
+    /// Start over, replacing any interrupted run.
+    Fresh,
+    /// Continue the interrupted run.
+    Resume,
+    /// Leave the checkpoint alone, for previews and background runs.
+    Off,
+}
+
+/// Emits `scraping-progress` events to the app window.
+pub(crate) struct WindowSink(pub Window);
+
+impl ProgressSink for WindowSink {
+    fn send(&self, progress: &CombinedProgress) {
+        self.0
+            .emit("scraping-progress", progress)
+            .unwrap_or_else(|e| tracing::warn!(error = %e, "Failed to emit progress"));
+    }
+}
+
+/// Who started a run, which decides what is kept of it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunKind {
+    /// A scrape or retry the user started; "Retry failed" works on the last one.
+    Manual,
+    /// A scheduled run, of which only the report is kept.
+    Background,
+    /// A preview, which keeps nothing: no report, and no revisions for the
+    /// engram cache.
+    Preview,
+}
+
+/// Runs a scrape and, except for previews, keeps its report, and for manual
+/// runs its output. With `retry`, only the units that failed in that earlier
+/// outcome are scraped, and the result is that outcome with whatever the
+/// retry recovered merged in.
+pub(crate) async fn run_scrape(
+    app: AppHandle,
+    progress: impl ProgressSink + 'static,
+    kind: RunKind,
+    mode: CheckpointMode,
+    retry: Option<ScrapeOutcome>,
+) -> Result<ScrapeOutcome, String> {
+    let lock = app.state::<ScrapeLock>();
+    let _running = lock
+        .0
+        .try_lock()
+        .map_err(|_| "A scrape is already running".to_string())?;
+
+    let settings = read_settings(&app)?;
+    let checkpoint_path = get_checkpoint_path(&app);
+
+    // One client for the whole run so every scraper shares the same rate limit
+    let client = ScraperClient::new(&settings.scraper)
+        .map_err(|e| format!("Invalid scraper configuration: {}", e))?;
+    let inputs = ScrapeInputs {
+        checkpoint: match mode {
+            CheckpointMode::Fresh => Checkpoint::start(checkpoint_path),
+            CheckpointMode::Resume => Checkpoint::resume(checkpoint_path)?,
+            CheckpointMode::Off => Checkpoint::disabled(),
+        },
+        rules: SkipRules::compile(&read_skip_rules(&app)?)?,
+        mods: read_mod_map(&app)?,
+        baseline: previous_baseline(&app, settings.scraper.min_entry_ratio)?,
+        icons_file: app.path_resolver().resolve_resource("resources/icons.txt"),
+        revisions: match kind {
+            RunKind::Preview => RevisionCache::load_read_only(get_revision_cache_path(&app)),
+            _ => RevisionCache::load(get_revision_cache_path(&app)),
+        },
+        edition: settings.scraper.edition,
+        retry: retry
+            .as_ref()
+            .map(|previous| previous.report.failed_units()),
+    };
 
     // Spawn the scraping task to ensure proper thread handling
-    tokio::task::spawn({
-        let window = Arc::clone(&window);
-        async move {
-            scrapers::scrape_all((*window).clone())
-                .await
-                .map_err(|e| format!("Failed to scrape data: {}", e))
+    let outcome = tokio::task::spawn(async move {
+        scrapers::scrape_all(progress, client, &ScraperRegistry::default(), inputs).await
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
+    let outcome = match retry {
+        Some(mut previous) => {
+            previous.merge_retry(outcome);
+            previous
+        }
+        None => outcome,
+    };
+
+    match kind {
+        RunKind::Manual => {
+            save_report(&app, &outcome.report)?;
+            write_last_scrape(&app, &outcome)?;
         }
+        RunKind::Background => {
+            save_report(&app, &outcome.report)?;
+        }
+        RunKind::Preview => {}
+    }
+
+    match &outcome.report.error {
+        Some(error) => Err(format!("Failed to scrape data: {}", error)),
+        None => Ok(outcome),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn start_scraping(window: Window) -> Result<ScrapeOutcome, String> {
+    run_scrape(
+        window.app_handle(),
+        WindowSink(window),
+        RunKind::Manual,
+        CheckpointMode::Fresh,
+        None,
+    )
+    .await
+}
+
+/// Continues the last interrupted run from its checkpoint.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn resume_scraping(window: Window) -> Result<ScrapeOutcome, String> {
+    run_scrape(
+        window.app_handle(),
+        WindowSink(window),
+        RunKind::Manual,
+        CheckpointMode::Resume,
+        None,
+    )
+    .await
+}
+
+/// Runs a scrape and compares it with the saved database without changing
+/// either. Nothing else is touched either: the interrupted-run checkpoint,
+/// the engram revision cache and the saved reports stay as they were.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn preview_scraping(window: Window) -> Result<ScrapePreview, String> {
+    let app = window.app_handle();
+    let outcome = run_scrape(
+        app.clone(),
+        WindowSink(window),
+        RunKind::Preview,
+        CheckpointMode::Off,
+        None,
+    )
+    .await?;
+    let changes = diff::diff(&read_ark_data(&app)?, &outcome.data);
+
+    Ok(ScrapePreview {
+        report: outcome.report,
+        changes,
     })
+}
+
+/// Scrapes again only the item sections and engram pages that failed in the
+/// last manual run, and returns that run's output with what they yielded added.
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn retry_failed_units(window: Window) -> Result<ScrapeOutcome, String> {
+    let app = window.app_handle();
+    let previous = read_last_scrape(&app)?;
+    if previous.report.failed_units().is_empty() {
+        return Err("The last scrape has no failed units a retry can recover".to_string());
+    }
+
+    run_scrape(
+        app,
+        WindowSink(window),
+        RunKind::Manual,
+        CheckpointMode::Off,
+        Some(previous),
+    )
     .await
-    .unwrap_or_else(|e| Err(format!("Task failed: {}", e)))
+}
+
+/// Summaries of persisted scrape reports, newest first.
+#[tauri::command]
+pub async fn list_scrape_reports<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<Vec<ScrapeReportSummary>, String> {
+    Ok(read_reports(&app)?
+        .into_iter()
+        .map(|(id, report)| ScrapeReportSummary::new(id, &report))
+        .collect())
 }
 
 #[tauri::command]
+#[tracing::instrument(skip(app), err)]
+pub async fn load_scrape_report<R: Runtime>(
+    app: AppHandle<R>,
+    id: String,
+) -> Result<ScrapeReport, String> {
+    // Report ids are plain file names; refuse anything that walks out of the directory
+    if id.contains('/') || id.contains('\\') || id.contains("..") {
+        return Err("Invalid report id".to_string());
+    }
+
+    let json = fs::read_to_string(get_reports_dir(&app).join(&id))
+        .map_err(|e| format!("Failed to read scrape report: {}", e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse scrape report: {}", e))
+}
+
+#[tauri::command]
+pub async fn list_scrapers() -> Result<Vec<ScraperInfo>, String> {
+    Ok(ScraperRegistry::default().infos())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn merge_scraped_data(
     existing_data: ArkData,
     scraped_data: ArkData,
 ) -> Result<ArkData, String> {
     let mut merged = existing_data;
-
-    // Merge each category
-    for (key, value) in scraped_data.creatures {
-        merged.creatures.entry(key).or_insert(value);
-    }
-    for (key, value) in scraped_data.items {
-        merged.items.entry(key).or_insert(value);
-    }
-    for (key, value) in scraped_data.engrams {
-        merged.engrams.entry(key).or_insert(value);
-    }
-    for (key, value) in scraped_data.beacons {
-        merged.beacons.entry(key).or_insert(value);
-    }
-    for (key, value) in scraped_data.colors {
-        merged.colors.entry(key).or_insert(value);
-    }
-
+    merged.merge_missing(scraped_data);
     Ok(merged)
 }
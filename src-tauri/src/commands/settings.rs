@@ -0,0 +1,46 @@
+use super::data::{ensure_directory, get_app_dir};
+use crate::types::settings::Settings;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+
+fn get_settings_file_path<R: Runtime>(app: &AppHandle<R>) -> PathBuf {
+    let app_dir = get_app_dir(app);
+    ensure_directory(&app_dir).expect("failed to create app data directory");
+    app_dir.join("settings.json")
+}
+
+pub(crate) fn read_settings<R: Runtime>(app: &AppHandle<R>) -> Result<Settings, String> {
+    let settings_path = get_settings_file_path(app);
+
+    if !settings_path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let json = fs::read_to_string(&settings_path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn load_settings<R: Runtime>(app: AppHandle<R>) -> Result<Settings, String> {
+    read_settings(&app)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn save_settings<R: Runtime>(
+    app: AppHandle<R>,
+    settings: Settings,
+) -> Result<(), String> {
+    settings.scraper.validate()?;
+    settings.schedule.schedule.validate()?;
+
+    let settings_path = get_settings_file_path(&app);
+
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&settings_path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
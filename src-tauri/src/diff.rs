@@ -0,0 +1,149 @@
+//! Entry-level comparison of two `ArkData` databases.
+
+use crate::scrapers::registry::Category;
+use crate::types::ArkData;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// An entry present on both sides with different contents.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangedEntry {
+    pub old: Value,
+    pub new: Value,
+    /// Names of the fields that differ.
+    pub fields: Vec<String>,
+}
+
+/// Entries that differ between two versions of one collection, by key.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CollectionDiff {
+    pub added: BTreeMap<String, Value>,
+    /// Entries only the old side has, e.g. ones a scrape no longer found.
+    pub removed: BTreeMap<String, Value>,
+    pub changed: BTreeMap<String, ChangedEntry>,
+}
+
+impl CollectionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Differences per collection; collections without any are left out.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DataDiff {
+    pub collections: BTreeMap<Category, CollectionDiff>,
+}
+
+impl DataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.collections.is_empty()
+    }
+}
+
+/// What changed going from `old` to `new`.
+pub fn diff(old: &ArkData, new: &ArkData) -> DataDiff {
+    let mut result = DataDiff::default();
+    add_collection(
+        &mut result,
+        Category::Creatures,
+        &old.creatures,
+        &new.creatures,
+    );
+    add_collection(&mut result, Category::Items, &old.items, &new.items);
+    add_collection(&mut result, Category::Engrams, &old.engrams, &new.engrams);
+    add_collection(&mut result, Category::Beacons, &old.beacons, &new.beacons);
+    add_collection(&mut result, Category::Colors, &old.colors, &new.colors);
+    add_collection(&mut result, Category::Icons, &old.icons, &new.icons);
+    result
+}
+
+fn add_collection<T: Serialize>(
+    result: &mut DataDiff,
+    category: Category,
+    old: &HashMap<String, T>,
+    new: &HashMap<String, T>,
+) {
+    let mut collection = CollectionDiff::default();
+
+    for (key, entry) in new {
+        let entry = to_value(entry);
+        match old.get(key).map(to_value) {
+            None => {
+                collection.added.insert(key.clone(), entry);
+            }
+            Some(previous) if previous != entry => {
+                let fields = changed_fields(&previous, &entry);
+                collection.changed.insert(
+                    key.clone(),
+                    ChangedEntry {
+                        old: previous,
+                        new: entry,
+                        fields,
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, entry) in old {
+        if !new.contains_key(key) {
+            collection.removed.insert(key.clone(), to_value(entry));
+        }
+    }
+
+    if !collection.is_empty() {
+        result.collections.insert(category, collection);
+    }
+}
+
+/// Entry types don't implement `PartialEq`, so compare their serialized form.
+fn to_value<T: Serialize>(entry: &T) -> Value {
+    serde_json::to_value(entry).unwrap_or(Value::Null)
+}
+
+fn changed_fields(old: &Value, new: &Value) -> Vec<String> {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut fields: Vec<String> = old
+                .keys()
+                .chain(new.keys())
+                .filter(|field| old.get(*field) != new.get(*field))
+                .cloned()
+                .collect();
+            fields.sort();
+            fields.dedup();
+            fields
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+
+    #[test]
+    fn diff_reports_added_changed_and_removed_entries() {
+        let mut old = ArkData::default();
+        for name in ["Stone", "Wood", "Flint"] {
+            old.items.insert(name.to_string(), item(name));
+        }
+        let mut new = ArkData::default();
+        new.items.insert("Stone".to_string(), item("Stone"));
+        let mut wood = item("Wood");
+        wood.mod_id = "scorched-earth".to_string();
+        new.items.insert("Wood".to_string(), wood);
+        new.items.insert("Metal".to_string(), item("Metal"));
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.collections.len(), 1);
+        let items = &changes.collections[&Category::Items];
+        assert_eq!(items.added.keys().collect::<Vec<_>>(), ["Metal"]);
+        assert_eq!(items.removed.keys().collect::<Vec<_>>(), ["Flint"]);
+        assert_eq!(items.changed["Wood"].fields, ["mod_id"]);
+        assert!(diff(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+//! Scraping, validation and comparison of `ArkData` databases, shared by the
+//! desktop app and the headless `arkdata` command line tool.
+
+pub mod diff;
+pub mod schedule;
+pub mod scrapers;
+pub mod types;
+pub mod validation;
+
+#[cfg(test)]
+mod test_support;
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "arkdata";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_FILTER: &str = "info,artis_arkdata_manager=debug";
+const DEFAULT_QUERY_LIMIT: usize = 500;
+
+/// Keeps the background log writer alive; dropping it flushes and stops logging.
+pub struct LogGuard {
+    _guard: WorkerGuard,
+}
+
+/// Sends JSON log records to a daily rotating file in `log_dir`, keeping the
+/// last [`MAX_LOG_FILES`] days. Debug builds also log to stdout. The filter can
+/// be overridden with the `ARKDATA_LOG` environment variable.
+pub fn init(log_dir: &Path) -> Result<LogGuard, Box<dyn std::error::Error + Send + Sync>> {
+    fs::create_dir_all(log_dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let filter =
+        EnvFilter::try_from_env("ARKDATA_LOG").unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let file_layer = fmt::layer()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(writer);
+    let console_layer = cfg!(debug_assertions).then(fmt::layer);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(console_layer)
+        .try_init()?;
+
+    Ok(LogGuard { _guard: guard })
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct LogQuery {
+    /// Least severe level to include, e.g. `"warn"` returns warnings and errors.
+    pub level: Option<String>,
+    /// Prefix of the module path that emitted the record.
+    pub target: Option<String>,
+    /// Case-insensitive text that must appear in the message or fields.
+    pub contains: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogRecord {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: Value,
+    /// Names of the spans the record was emitted in, outermost first.
+    pub spans: Vec<String>,
+}
+
+/// Reads the newest records from the log files in `log_dir` that match `query`,
+/// newest first.
+pub fn query(log_dir: &Path, query: &LogQuery) -> Result<Vec<LogRecord>, String> {
+    let min_level = match &query.level {
+        Some(level) => Some(
+            level
+                .parse::<Level>()
+                .map_err(|_| format!("Invalid log level: {}", level))?,
+        ),
+        None => None,
+    };
+    let contains = query.contains.as_ref().map(|c| c.to_lowercase());
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+    let mut files: Vec<_> = fs::read_dir(log_dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.file_name().and_then(|name| name.to_str()),
+                Some(name) if name.starts_with(LOG_FILE_PREFIX)
+            )
+        })
+        .collect();
+
+    // Rotated files carry the date in their name, so reverse order is newest first
+    files.sort();
+    files.reverse();
+
+    let mut records = Vec::new();
+    for file in files {
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        for line in content.lines().rev() {
+            let record = match parse_record(line) {
+                Some(record) => record,
+                None => continue,
+            };
+
+            if let Some(min_level) = min_level {
+                // `Level` orders TRACE as the greatest, so "at least as severe" is `<=`
+                match record.level.parse::<Level>() {
+                    Ok(level) if level <= min_level => {}
+                    _ => continue,
+                }
+            }
+
+            if let Some(target) = &query.target {
+                if !record.target.starts_with(target.as_str()) {
+                    continue;
+                }
+            }
+
+            if let Some(needle) = &contains {
+                let haystack = format!("{} {}", record.message, record.fields).to_lowercase();
+                if !haystack.contains(needle.as_str()) {
+                    continue;
+                }
+            }
+
+            records.push(record);
+            if records.len() >= limit {
+                return Ok(records);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+fn parse_record(line: &str) -> Option<LogRecord> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+
+    let mut fields = value
+        .get_mut("fields")
+        .map(Value::take)
+        .unwrap_or(Value::Null);
+    let message = fields
+        .as_object_mut()
+        .and_then(|fields| fields.remove("message"))
+        .and_then(|message| message.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let spans = value
+        .get("spans")
+        .and_then(Value::as_array)
+        .map(|spans| {
+            spans
+                .iter()
+                .filter_map(|span| span.get("name").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let text = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    Some(LogRecord {
+        timestamp: text("timestamp"),
+        level: text("level"),
+        target: text("target"),
+        message,
+        fields,
+        spans,
+    })
+}
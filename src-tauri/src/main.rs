@@ -1,29 +1,69 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
-mod scrapers;
-mod types;
+mod logging;
 
+use artis_arkdata_manager::{diff, schedule, scrapers, types, validation};
 use commands::*;
 use tauri::Manager;
 
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_context_menu::init())
+        .manage(ScrapeLock::default())
         .invoke_handler(tauri::generate_handler![
             // Data commands
             load_ark_data,
             save_ark_data,
             export_data,
+            filter_by_edition,
             import_data,
             create_backup,
+            migrate_keys,
+            // Settings commands
+            load_settings,
+            save_settings,
             // Scraping commands
             start_scraping,
+            resume_scraping,
+            preview_scraping,
+            retry_failed_units,
+            list_scrapers,
+            list_scrape_reports,
+            load_scrape_report,
             merge_scraped_data,
+            // Scheduled scraping commands
+            get_pending_changes,
+            apply_pending_changes,
+            discard_pending_changes,
+            // Skip rule commands
+            load_skip_rules,
+            save_skip_rules,
+            reset_skip_rules,
+            // Mod map commands
+            load_mod_map,
+            save_mod_map,
+            reset_mod_map,
+            list_unknown_mods,
+            migrate_mods,
+            mod_registry_entry,
             // Validation commands
             validate_entry,
+            parse_blueprint,
+            entry_key,
+            // Log commands
+            query_logs,
         ])
         .setup(|app| {
+            match logging::init(&get_logs_dir(&app.handle())) {
+                Ok(guard) => {
+                    app.manage(guard);
+                }
+                Err(e) => eprintln!("Failed to initialise logging: {}", e),
+            }
+
+            spawn_scheduler(app.handle());
+
             #[cfg(debug_assertions)]
             {
                 let main_window = app.get_window("main").unwrap();
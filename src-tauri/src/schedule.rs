@@ -0,0 +1,253 @@
+//! When background scrapes run. A schedule is either a fixed interval or a
+//! cron-style expression evaluated in local time.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// Scheduled scrapes are searched for at most this far ahead.
+const MAX_LOOKAHEAD_DAYS: i64 = 366;
+
+/// Longest interval between scheduled scrapes, one lookahead period.
+const MAX_INTERVAL_HOURS: f32 = (MAX_LOOKAHEAD_DAYS * 24) as f32;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Schedule {
+    /// Every `hours` hours after the previous scheduled run.
+    Interval { hours: f32 },
+    /// Standard five-field cron expression: minute, hour, day of month,
+    /// month and day of week (0 or 7 is Sunday), e.g. `0 4 * * 1` for
+    /// Mondays at 04:00.
+    Cron { expression: String },
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule::Interval { hours: 24.0 }
+    }
+}
+
+impl Schedule {
+    /// Fails when the interval is not a positive number of hours up to a year,
+    /// or the expression does not parse.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Schedule::Interval { hours } if valid_interval(*hours) => Ok(()),
+            Schedule::Interval { hours } => Err(format!(
+                "Invalid schedule interval: {} hours, expected more than 0 and at most {}",
+                hours, MAX_INTERVAL_HOURS
+            )),
+            Schedule::Cron { expression } => CronExpr::parse(expression).map(|_| ()),
+        }
+    }
+
+    /// The first scheduled time strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Schedule::Interval { hours } if valid_interval(*hours) => {
+                after.checked_add_signed(Duration::seconds((*hours * 3600.0).round() as i64))
+            }
+            Schedule::Interval { .. } => None,
+            Schedule::Cron { expression } => CronExpr::parse(expression).ok()?.next_after(after),
+        }
+    }
+}
+
+fn valid_interval(hours: f32) -> bool {
+    hours.is_finite() && hours > 0.0 && hours <= MAX_INTERVAL_HOURS
+}
+
+/// Background scraping preferences, part of the app settings.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub enabled: bool,
+    pub schedule: Schedule,
+    /// Merge what a scheduled scrape found straight into the database instead
+    /// of keeping it as pending changes for review.
+    pub auto_merge: bool,
+}
+
+/// A parsed cron expression; each field lists the values it matches.
+#[derive(Debug, Clone)]
+pub struct CronExpr {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    weekdays: Vec<u32>,
+    /// Cron matches a day when either day field matches, unless one is `*`.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Invalid cron expression '{}': expected 5 fields, found {}",
+                expression,
+                fields.len()
+            ));
+        }
+
+        let field = |index: usize, min: u32, max: u32| {
+            parse_field(fields[index], min, max)
+                .map_err(|e| format!("Invalid cron expression '{}': {}", expression, e))
+        };
+
+        let mut weekdays = field(4, 0, 7)?;
+        // 7 is another name for Sunday
+        if weekdays.contains(&7) {
+            weekdays.retain(|day| *day != 7);
+            if !weekdays.contains(&0) {
+                weekdays.insert(0, 0);
+            }
+        }
+
+        Ok(Self {
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    /// The first matching minute strictly after `after`. Works on wall-clock
+    /// time, so a match inside a skipped DST hour is passed over.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let after = after.naive_local();
+        let start =
+            after.date().and_hms_opt(after.hour(), after.minute(), 0)? + Duration::minutes(1);
+        let end = start + Duration::days(MAX_LOOKAHEAD_DAYS);
+
+        let mut time = start;
+        while time < end {
+            if !self.matches_day(&time) {
+                time = (time.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours.contains(&time.hour()) {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if self.minutes.contains(&time.minute()) {
+                if let Some(local) = Local.from_local_datetime(&time).earliest() {
+                    return Some(local);
+                }
+            }
+            time += Duration::minutes(1);
+        }
+
+        None
+    }
+
+    fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        if !self.months.contains(&time.month()) {
+            return false;
+        }
+
+        let day = self.days.contains(&time.day());
+        let weekday = self
+            .weekdays
+            .contains(&time.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+/// Parses one field: `*`, `n`, `a-b`, any of those with `/step`, or a
+/// comma-separated list of them.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    let mut values = Vec::new();
+
+    for part in field.split(',') {
+        let stepped = part.contains('/');
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be at least 1".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (parse_value(start, min, max)?, parse_value(end, min, max)?),
+                // `n/step` runs from n to the end of the range
+                None if stepped => (parse_value(range, min, max)?, max),
+                None => {
+                    let value = parse_value(range, min, max)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("empty range '{}'", range));
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    match value.parse() {
+        Ok(value) if (min..=max).contains(&value) => Ok(value),
+        _ => Err(format!("'{}' is not between {} and {}", value, min, max)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+
+    #[test]
+    fn cron_schedule_finds_the_next_matching_minute() {
+        let weekly = Schedule::Cron {
+            expression: "0 4 * * 1".to_string(),
+        };
+        assert!(weekly.validate().is_ok());
+
+        // Wednesday 2024-05-15 12:30 local time
+        let after = Local.with_ymd_and_hms(2024, 5, 15, 12, 30, 0).unwrap();
+        let next = weekly.next_after(after).unwrap();
+        assert_eq!(next.weekday(), Weekday::Mon);
+        assert_eq!((next.day(), next.hour(), next.minute()), (20, 4, 0));
+
+        let every_15 = Schedule::Cron {
+            expression: "*/15 * * * *".to_string(),
+        };
+        assert_eq!(every_15.next_after(after).unwrap().minute(), 45);
+
+        for expression in ["0 4 * *", "60 * * * *", "*/0 * * * *", "5-1 * * * *"] {
+            let schedule = Schedule::Cron {
+                expression: expression.to_string(),
+            };
+            assert!(schedule.validate().is_err(), "{}", expression);
+        }
+        for hours in [0.0, -1.0, f32::NAN, f32::INFINITY, 1e9] {
+            let interval = Schedule::Interval { hours };
+            assert!(interval.validate().is_err(), "{}", hours);
+            assert!(interval.next_after(Local::now()).is_none(), "{}", hours);
+        }
+    }
+}
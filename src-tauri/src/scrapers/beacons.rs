@@ -1,16 +1,46 @@
+use super::columns::{matching_tables, Column};
+use super::layout::expect_at_least;
+use super::mods::UNKNOWN_MOD;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper};
+use super::rules::RowFields;
 use super::{common::*, progress::ScrapingProgress};
 use crate::types::Beacon;
+use async_trait::async_trait;
 use scraper::{ElementRef, Html, Selector};
-use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::Window;
-
-pub async fn scrape_beacons(
-    window: &Window,
-    beacons: &mut HashMap<String, Beacon>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = ScraperClient::new();
-    let url = format!("{}/wiki/Beacon_IDs", BASE_URL);
+
+const NAME: &str = "Name";
+const CLASS_NAME: &str = "Class Name";
+const EDITION: &str = "Edition";
+
+const COLUMNS: &[Column] = &[
+    Column::required(NAME, &["Beacon"]),
+    Column::required(CLASS_NAME, &["Class", "ClassName"]),
+    Column::optional(EDITION, &["Game", "Availability"]),
+];
+
+pub struct BeaconScraper;
+
+#[async_trait]
+impl Scraper for BeaconScraper {
+    fn name(&self) -> &'static str {
+        "beacons"
+    }
+
+    fn category(&self) -> Category {
+        Category::Beacons
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_beacons(ctx, sink).await
+    }
+}
+
+pub async fn scrape_beacons(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+    let client = &ctx.client;
+
+    let url = client.url("/wiki/Beacon_IDs");
     let html = client.fetch_page(&url).await?;
     let document = Html::parse_document(&html);
 
@@ -20,18 +50,22 @@ pub async fn scrape_beacons(
     let heading_selector = Arc::new(Selector::parse("h3 .mw-headline").unwrap());
 
     let tables: Vec<_> = document.select(&table_selector).collect();
+    let tables = matching_tables(&tables, COLUMNS, &url);
+    expect_at_least(&url, "beacon tables", tables.len(), 1)?;
     let total_tables = tables.len();
 
-    let mut current_mod = String::from("Ark");
+    let mut current_mod = UNKNOWN_MOD.to_string();
+    let mut current_header = String::new();
 
-    for (table_idx, table) in tables.iter().enumerate() {
+    for (table_idx, (table, columns)) in tables.iter().enumerate() {
         // Look for the preceding h3 header
         let mut current_element = table.prev_sibling();
         while let Some(element) = current_element {
             if let Some(element_ref) = ElementRef::wrap(element) {
                 if element_ref.value().name() == "h3" {
                     if let Some(headline) = element_ref.select(&heading_selector).next() {
-                        current_mod = clean_name(&headline.text().collect::<String>());
+                        current_header = headline.text().collect();
+                        current_mod = ctx.mods.from_header(&current_header);
                     }
                     break;
                 }
@@ -45,7 +79,9 @@ pub async fn scrape_beacons(
         for (row_idx, row) in rows.iter().enumerate().skip(1) {
             let cells: Vec<_> = row.select(&cell_selector).collect();
 
-            if cells.len() >= 2 {
+            if let (Some(name_cell), Some(class_cell)) =
+                (columns.cell(&cells, NAME), columns.cell(&cells, CLASS_NAME))
+            {
                 let progress = ((table_idx as f32 / total_tables as f32)
                     + (row_idx as f32 / total_rows as f32 / total_tables as f32))
                     * 100.0;
@@ -60,39 +96,47 @@ pub async fn scrape_beacons(
                         current_mod
                     ),
                 )
-                .emit(window);
-
-                let name = clean_name(&cells[0].text().collect::<String>());
-
-                if !should_skip_beacon(&name) {
-                    // Split class names by <br> tag or newline
-                    let html_content = cells[1].inner_html();
-                    let class_names: Vec<String> = html_content
-                        .split(|c| c == '\n' || c == '<')
+                .items(sink.len())
+                .url(&url)
+                .emit(tracker);
+
+                let name = clean_name(&name_cell.text().collect::<String>());
+
+                let row = RowFields {
+                    name: &name,
+                    mod_id: Some(&current_mod),
+                    ..RowFields::default()
+                };
+
+                if let Some(rule) = ctx.rules.matching("beacons", &row) {
+                    sink.skip_by_rule(&name, rule, &url);
+                } else {
+                    // Cells with several class names separate them with <br>,
+                    // so each one is its own text node
+                    let class_names: Vec<String> = class_cell
+                        .text()
+                        .flat_map(str::lines)
+                        .map(clean_name)
                         .filter(|s| s.contains("_C"))
-                        .map(|s| clean_name(s))
                         .collect();
 
+                    let marker = columns
+                        .cell(&cells, EDITION)
+                        .map(|cell| cell.text().collect::<String>())
+                        .unwrap_or_else(|| current_header.clone());
+                    let editions = ctx.mods.editions(&marker, &current_mod, ctx.edition);
+
                     for (idx, class_name) in class_names.iter().enumerate() {
                         let suffix = if idx > 0 { " (Double)" } else { "" };
                         let display_name = format!("{}{}", name, suffix);
 
-                        // Create key with mod name prefix
-                        let key = format!(
-                            "{}_{}",
-                            current_mod.replace(" ", "_"),
-                            display_name.replace(" ", "_")
-                        );
-
-                        beacons.insert(
-                            key,
-                            Beacon {
-                                type_name: "beacon".to_string(),
-                                name: display_name,
-                                mod_name: current_mod.clone(),
-                                class_name: class_name.to_string(),
-                            },
-                        );
+                        sink.insert(Beacon {
+                            type_name: "beacon".to_string(),
+                            name: display_name,
+                            mod_id: current_mod.clone(),
+                            editions: editions.clone(),
+                            class_name: class_name.to_string(),
+                        });
                     }
                 }
             }
@@ -101,17 +145,3 @@ pub async fn scrape_beacons(
 
     Ok(())
 }
-
-fn should_skip_beacon(name: &str) -> bool {
-    let skip_patterns = [
-        "Genesis_2_",
-        "Genesis_1_Fishing",
-        "Choose_Your_Own_Adventure",
-        "Team_Downriver_Run",
-        "Starwing_Strike",
-        "Star_Dolphin",
-        "Slipstream_Sweep",
-        "Slide_and_Glide",
-    ];
-    skip_patterns.iter().any(|pattern| name.contains(pattern))
-}
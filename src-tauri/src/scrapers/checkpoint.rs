@@ -0,0 +1,172 @@
+use super::registry::ScrapeSink;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Unit checkpoints are flushed at most this often; stage completions always flush.
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Default)]
+struct CheckpointState {
+    started_at: i64,
+    /// Output of every stage that finished.
+    stages: HashMap<String, ScrapeSink>,
+    /// Output of finished units (item sections, engram pages) per unfinished stage.
+    units: HashMap<String, HashMap<String, ScrapeSink>>,
+}
+
+/// On-disk record of an in-progress scrape, so an interrupted run can pick up
+/// where it stopped instead of starting over.
+pub struct Checkpoint {
+    path: Option<PathBuf>,
+    state: Mutex<CheckpointState>,
+    last_save: Mutex<Instant>,
+}
+
+impl Checkpoint {
+    /// A checkpoint that only lives in memory.
+    pub fn disabled() -> Self {
+        Self::with_state(None, CheckpointState::default())
+    }
+
+    /// Begins a fresh run at `path`, discarding any earlier checkpoint.
+    pub fn start(path: PathBuf) -> Self {
+        let checkpoint = Self::with_state(
+            Some(path),
+            CheckpointState {
+                started_at: chrono::Utc::now().timestamp(),
+                ..CheckpointState::default()
+            },
+        );
+        checkpoint.save();
+        checkpoint
+    }
+
+    /// Continues the run recorded at `path`.
+    pub fn resume(path: PathBuf) -> Result<Self, String> {
+        let json =
+            fs::read_to_string(&path).map_err(|_| "No interrupted scrape to resume".to_string())?;
+        let state: CheckpointState = serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse scrape checkpoint: {}", e))?;
+
+        Ok(Self::with_state(Some(path), state))
+    }
+
+    fn with_state(path: Option<PathBuf>, state: CheckpointState) -> Self {
+        Self {
+            path,
+            state: Mutex::new(state),
+            last_save: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn completed_stage(&self, stage: &str) -> Option<ScrapeSink> {
+        let state = self.state.lock().unwrap();
+        state.stages.get(stage).cloned()
+    }
+
+    pub fn complete_stage(&self, stage: &str, sink: &ScrapeSink) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.stages.insert(stage.to_string(), sink.clone());
+            state.units.remove(stage);
+        }
+        self.save();
+    }
+
+    pub fn completed_unit(&self, stage: &str, unit: &str) -> Option<ScrapeSink> {
+        let state = self.state.lock().unwrap();
+        state
+            .units
+            .get(stage)
+            .and_then(|units| units.get(unit))
+            .cloned()
+    }
+
+    pub fn complete_unit(&self, stage: &str, unit: &str, sink: &ScrapeSink) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state
+                .units
+                .entry(stage.to_string())
+                .or_default()
+                .insert(unit.to_string(), sink.clone());
+        }
+
+        let mut last_save = self.last_save.lock().unwrap();
+        if last_save.elapsed() >= SAVE_INTERVAL {
+            *last_save = Instant::now();
+            drop(last_save);
+            self.save();
+        }
+    }
+
+    /// Writes the current state to disk.
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let json = {
+            let state = self.state.lock().unwrap();
+            serde_json::to_string(&*state)
+        };
+
+        match json {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    tracing::error!(error = %e, "Failed to write scrape checkpoint");
+                }
+            }
+            Err(e) => tracing::error!(error = %e, "Failed to serialize scrape checkpoint"),
+        }
+    }
+
+    /// Removes the checkpoint once the run has completed.
+    pub fn clear(&self) {
+        if let Some(path) = &self.path {
+            if path.exists() {
+                if let Err(e) = fs::remove_file(path) {
+                    tracing::warn!(error = %e, "Failed to remove scrape checkpoint");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::env;
+
+    #[test]
+    fn a_resumed_checkpoint_holds_the_units_and_stages_saved_before() {
+        let path = env::temp_dir().join(format!("checkpoint_units_{}.json", std::process::id()));
+        let mut stone = ScrapeSink::new();
+        stone.insert(test_support::item("Stone"));
+
+        let checkpoint = Checkpoint::start(path.clone());
+        checkpoint.complete_unit("items", "Stone", &stone);
+        checkpoint.save();
+
+        let resumed = Checkpoint::resume(path.clone()).unwrap();
+        assert!(resumed.completed_unit("items", "Stone").is_some());
+        assert!(resumed.completed_unit("items", "Wood").is_none());
+        assert!(resumed.completed_stage("items").is_none());
+
+        // A finished stage replaces its units
+        resumed.complete_stage("items", &stone);
+        let resumed = Checkpoint::resume(path.clone()).unwrap();
+        assert!(resumed.completed_stage("items").is_some());
+        assert!(resumed.completed_unit("items", "Stone").is_none());
+
+        resumed.clear();
+        assert!(!path.exists());
+        assert!(Checkpoint::resume(path).is_err());
+    }
+}
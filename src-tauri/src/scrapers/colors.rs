@@ -1,15 +1,32 @@
+use super::layout::expect_at_least;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper};
 use super::{common::*, progress::ScrapingProgress};
 use crate::types::Color;
+use async_trait::async_trait;
 use scraper::{Html, Selector};
-use std::collections::HashMap;
-use tauri::Window;
-
-pub async fn scrape_colors(
-    window: &Window,
-    colors: &mut HashMap<String, Color>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = ScraperClient::new();
-    let url = format!("{}/wiki/Color_IDs", BASE_URL);
+
+pub struct ColorScraper;
+
+#[async_trait]
+impl Scraper for ColorScraper {
+    fn name(&self) -> &'static str {
+        "colors"
+    }
+
+    fn category(&self) -> Category {
+        Category::Colors
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_colors(ctx, sink).await
+    }
+}
+
+pub async fn scrape_colors(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+    let client = &ctx.client;
+
+    let url = client.url("/wiki/Color_IDs");
     let html = client.fetch_page(&url).await?;
     let document = Html::parse_document(&html);
 
@@ -26,6 +43,13 @@ pub async fn scrape_colors(
         .map(|list| list.select(&item_selector).count())
         .sum();
 
+    expect_at_least(
+        &url,
+        "color lists",
+        document.select(&list_selector).count(),
+        1,
+    )?;
+
     // Find all color lists
     let color_lists = document.select(&list_selector);
     let mut processed_colors = 0;
@@ -33,7 +57,7 @@ pub async fn scrape_colors(
     for list in color_lists {
         let items = list.select(&item_selector);
 
-        for (_index, item) in items.enumerate() {
+        for item in items {
             // Extract color ID
             let color_id = item
                 .select(&id_selector)
@@ -72,19 +96,18 @@ pub async fn scrape_colors(
                         processed_colors, total_items, name
                     ),
                 )
-                .emit(window);
-
-                // Create the color entry
-                let key = name.replace(' ', "_").replace('-', "_");
-                colors.insert(
-                    key,
-                    Color {
-                        type_name: "color".to_string(),
-                        name,
-                        color_id,
-                        hex_code,
-                    },
-                );
+                .items(processed_colors)
+                .url(&url)
+                .emit(tracker);
+
+                sink.insert(Color {
+                    type_name: "color".to_string(),
+                    name,
+                    color_id,
+                    hex_code,
+                });
+            } else {
+                sink.skip(&name, "missing or invalid color ID", &url);
             }
         }
     }
@@ -93,9 +116,9 @@ pub async fn scrape_colors(
     ScrapingProgress::new(
         "colors",
         100.0,
-        &format!("Completed processing {} colors", colors.len()),
+        &format!("Completed processing {} colors", sink.len()),
     )
-    .emit(window);
+    .emit(tracker);
 
     Ok(())
 }
@@ -0,0 +1,218 @@
+use scraper::{ElementRef, Selector};
+use std::error::Error;
+use std::fmt;
+
+/// A column a scraper reads from a wiki table, matched by header text.
+pub struct Column {
+    /// Canonical header, used in errors and to look the column up.
+    pub name: &'static str,
+    /// Other header texts the wiki has used for the same column.
+    pub aliases: &'static [&'static str],
+    pub required: bool,
+}
+
+impl Column {
+    pub const fn required(name: &'static str, aliases: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            aliases,
+            required: true,
+        }
+    }
+
+    pub const fn optional(name: &'static str, aliases: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            aliases,
+            required: false,
+        }
+    }
+
+    fn matches(&self, header: &str) -> bool {
+        header.eq_ignore_ascii_case(self.name)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| header.eq_ignore_ascii_case(alias))
+    }
+}
+
+/// Raised when a table lacks a column the scraper cannot do without.
+#[derive(Debug)]
+pub struct MissingColumns {
+    pub url: String,
+    pub missing: Vec<&'static str>,
+    pub headers: Vec<String>,
+}
+
+impl fmt::Display for MissingColumns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "table on {} is missing required column(s) {} (found headers: {})",
+            self.url,
+            self.missing.join(", "),
+            if self.headers.is_empty() {
+                "none".to_string()
+            } else {
+                self.headers.join(", ")
+            }
+        )
+    }
+}
+
+impl Error for MissingColumns {}
+
+/// The tables among `tables` that have every required column, each with its
+/// column map. Other wikitables on a page, such as navigation boxes or notes,
+/// are skipped, so callers check that at least one matched.
+pub fn matching_tables<'a>(
+    tables: &[ElementRef<'a>],
+    columns: &[Column],
+    url: &str,
+) -> Vec<(ElementRef<'a>, ColumnMap)> {
+    tables
+        .iter()
+        .filter_map(|table| match ColumnMap::from_table(table, columns, url) {
+            Ok(map) => Some((*table, map)),
+            Err(e) => {
+                tracing::debug!(reason = %e, "Skipping table");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Maps the columns a scraper wants to their positions in one table.
+pub struct ColumnMap {
+    columns: Vec<(&'static str, usize)>,
+    width: usize,
+}
+
+impl ColumnMap {
+    /// Reads the first header row of `table` and locates each of `columns`.
+    pub fn from_table(
+        table: &ElementRef,
+        columns: &[Column],
+        url: &str,
+    ) -> Result<Self, MissingColumns> {
+        let headers = header_texts(table);
+
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for column in columns {
+            match headers.iter().position(|header| column.matches(header)) {
+                Some(index) => found.push((column.name, index)),
+                None if column.required => missing.push(column.name),
+                None => {}
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(MissingColumns {
+                url: url.to_string(),
+                missing,
+                headers,
+            });
+        }
+
+        Ok(Self {
+            columns: found,
+            width: headers.len(),
+        })
+    }
+
+    /// Whether `cells` spans the whole header. Shorter rows are spacers or
+    /// use `rowspan`, so their cells do not line up with the header.
+    pub fn fits(&self, cells: &[ElementRef]) -> bool {
+        cells.len() >= self.width
+    }
+
+    /// The cell under the column named `name`, if the table has one.
+    pub fn cell<'a>(&self, cells: &[ElementRef<'a>], name: &str) -> Option<ElementRef<'a>> {
+        self.columns
+            .iter()
+            .find(|(column, _)| *column == name)
+            .and_then(|(_, index)| cells.get(*index).copied())
+    }
+}
+
+fn header_texts(table: &ElementRef) -> Vec<String> {
+    let row_selector = Selector::parse("tr").unwrap();
+    let header_selector = Selector::parse("th").unwrap();
+
+    table
+        .select(&row_selector)
+        .map(|row| {
+            row.select(&header_selector)
+                .map(|th| {
+                    th.text()
+                        .collect::<String>()
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|headers| !headers.is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    #[test]
+    fn tables_missing_required_columns_are_refused() {
+        let html = Html::parse_fragment(
+            r#"<table class="wikitable">
+                <tr><th>Blueprint Path</th><th>Creature</th></tr>
+                <tr><td>"Blueprint'/Game/Dodo.Dodo'"</td><td>Dodo</td></tr>
+            </table>"#,
+        );
+        let table = html
+            .select(&Selector::parse("table").unwrap())
+            .next()
+            .unwrap();
+        let columns = [
+            Column::required("Creature", &[]),
+            Column::required("Entity ID", &["ID"]),
+            Column::required("Blueprint Path", &["Blueprint"]),
+        ];
+
+        let error = ColumnMap::from_table(&table, &columns, "test")
+            .err()
+            .expect("table without an entity ID column was accepted");
+        assert_eq!(error.missing, vec!["Entity ID"]);
+
+        // Reordered columns are still found by header
+        let columns = ColumnMap::from_table(&table, &columns[..1], "test").unwrap();
+        let row = html
+            .select(&Selector::parse("td").unwrap())
+            .collect::<Vec<_>>();
+        let name = columns.cell(&row, "Creature").unwrap();
+        assert_eq!(name.text().collect::<String>(), "Dodo");
+    }
+
+    #[test]
+    fn unrelated_tables_are_skipped_instead_of_failing_the_page() {
+        let html = Html::parse_fragment(
+            r#"<table class="wikitable"><tr><th>Notes</th></tr><tr><td>See also</td></tr></table>
+            <table class="wikitable">
+                <tr><th>Creature</th><th>Blueprint Path</th></tr>
+                <tr><td>Dodo</td><td>"Blueprint'/Game/Dodo.Dodo'"</td></tr>
+            </table>"#,
+        );
+        let tables: Vec<_> = html.select(&Selector::parse("table").unwrap()).collect();
+        let columns = [
+            Column::required("Creature", &[]),
+            Column::required("Blueprint Path", &[]),
+        ];
+
+        let matching = matching_tables(&tables, &columns, "test");
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].0, tables[1]);
+        assert!(matching_tables(&tables[..1], &columns, "test").is_empty());
+    }
+}
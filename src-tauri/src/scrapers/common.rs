@@ -1,70 +1,324 @@
+use super::config::ScraperConfig;
+use super::fixtures::{self, FixtureMode};
+use crate::types::blueprint::Blueprint;
+use rand::Rng;
 use regex::Regex;
-use reqwest::{Client, ClientBuilder};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Certificate, Client, ClientBuilder, Proxy, Response, StatusCode};
+use std::fmt;
+use std::fs;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
 
 pub const BASE_URL: &str = "https://ark.wiki.gg";
 
+/// Requests per second shared by every scraper unless configured otherwise.
+pub const DEFAULT_REQUESTS_PER_SECOND: f32 = 2.0;
+
+const MAX_ATTEMPTS: u32 = 4;
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum FetchError {
+    /// The page does not exist (404/410). Never retried.
+    NotFound { url: String },
+    /// The server asked us to slow down (429/503) and kept doing so.
+    Throttled {
+        url: String,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+    },
+    /// Any other non-success status.
+    Status { url: String, status: StatusCode },
+    /// Connection, timeout or body read failure.
+    Network { url: String, source: reqwest::Error },
+}
+
+impl FetchError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            FetchError::NotFound { .. } => false,
+            FetchError::Throttled { .. } | FetchError::Network { .. } => true,
+            FetchError::Status { status, .. } => status.is_server_error(),
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NotFound { url } => write!(f, "page not found: {}", url),
+            FetchError::Throttled {
+                url,
+                status,
+                retry_after,
+            } => match retry_after {
+                Some(wait) => write!(
+                    f,
+                    "throttled by server ({}) for {}, retry after {}s",
+                    status,
+                    url,
+                    wait.as_secs()
+                ),
+                None => write!(f, "throttled by server ({}) for {}", status, url),
+            },
+            FetchError::Status { url, status } => {
+                write!(f, "unexpected status {} for {}", status, url)
+            }
+            FetchError::Network { url, source } => {
+                write!(f, "network error for {}: {}", url, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchError::Network { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Spaces requests evenly so that all scrapers sharing it stay under a fixed
+/// request rate, and lets a throttled response pause everyone at once.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// `requests_per_second` is expected to have passed
+    /// [`ScraperConfig::validate`].
+    pub fn new(requests_per_second: f32) -> Self {
+        let interval = if requests_per_second > 0.0 {
+            Duration::from_secs_f32(1.0 / requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Holds back every caller until `wait` has elapsed.
+    pub async fn pause_for(&self, wait: Duration) {
+        let mut next_slot = self.next_slot.lock().await;
+        let resume_at = Instant::now() + wait;
+        if resume_at > *next_slot {
+            *next_slot = resume_at;
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ScraperClient {
     client: Arc<Client>,
+    limiter: Arc<RateLimiter>,
+    base_url: String,
+    mirrors: Vec<String>,
+    fixtures: FixtureMode,
 }
 
 impl ScraperClient {
-    pub fn new() -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(30))
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
-            .build()
-            .unwrap();
+    pub fn new(config: &ScraperConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        config.validate()?;
 
-        Self {
-            client: Arc::new(client),
+        let mut builder = ClientBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(&config.user_agent);
+
+        if let Some(proxy) = config.proxy.as_deref().filter(|p| !p.is_empty()) {
+            builder = builder.proxy(Proxy::all(proxy)?);
         }
+
+        if let Some(path) = config.ca_certificate.as_deref().filter(|p| !p.is_empty()) {
+            let pem = fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate {}: {}", path, e))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+
+        Ok(Self {
+            client: Arc::new(builder.build()?),
+            limiter: Arc::new(RateLimiter::new(config.requests_per_second)),
+            base_url: trim_root(&config.base_url),
+            mirrors: config.mirrors.iter().map(|m| trim_root(m)).collect(),
+            fixtures: config.fixtures.clone(),
+        })
     }
 
-    pub async fn fetch_page(&self, url: &str) -> Result<String, reqwest::Error> {
-        let mut retries = 3;
-
-        while retries > 0 {
-            match self.client.get(url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        match response.text().await {
-                            Ok(text) => return Ok(text),
-                            Err(e) => {
-                                retries -= 1;
-                                if retries == 0 {
-                                    return Err(e);
-                                }
-                            }
-                        }
-                    } else {
-                        retries -= 1;
-                        if retries == 0 {
-                            return Err(response.error_for_status().unwrap_err());
-                        }
-                    }
-                }
-                Err(e) => {
-                    retries -= 1;
-                    if retries == 0 {
-                        return Err(e);
-                    }
+    /// Absolute URL for a wiki path such as `/wiki/Creature_IDs`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Fetches `url`, falling back to each configured mirror when the base
+    /// URL keeps failing. A missing page is never retried on a mirror.
+    ///
+    /// In replay mode the page comes from the fixture directory instead, and
+    /// a missing fixture is reported as [`FetchError::NotFound`].
+    pub async fn fetch_page(&self, url: &str) -> Result<String, FetchError> {
+        let relative_url = url.strip_prefix(&self.base_url).unwrap_or(url);
+
+        if let FixtureMode::Replay { dir } = &self.fixtures {
+            return fixtures::read_fixture(dir, relative_url).map_err(|_| FetchError::NotFound {
+                url: url.to_string(),
+            });
+        }
+
+        let result = self.fetch_live(url).await;
+
+        if let (FixtureMode::Record { dir }, Ok(body)) = (&self.fixtures, &result) {
+            if let Err(e) = fixtures::write_fixture(dir, relative_url, body) {
+                tracing::warn!(url, error = %e, "Failed to record fixture");
+            }
+        }
+
+        result
+    }
+
+    async fn fetch_live(&self, url: &str) -> Result<String, FetchError> {
+        let mut result = self.fetch_with_retries(url).await;
+
+        if let Some(path) = url.strip_prefix(&self.base_url) {
+            for mirror in &self.mirrors {
+                match &result {
+                    Ok(_) | Err(FetchError::NotFound { .. }) => break,
+                    Err(_) => {}
                 }
+                result = self
+                    .fetch_with_retries(&format!("{}{}", mirror, path))
+                    .await;
             }
+        }
+
+        result
+    }
 
-            if retries > 0 {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+    async fn fetch_with_retries(&self, url: &str) -> Result<String, FetchError> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            self.limiter.acquire().await;
+
+            let error = match self.client.get(url).send().await {
+                Ok(response) => match classify_response(url, response) {
+                    Ok(response) => match response.text().await {
+                        Ok(text) => return Ok(text),
+                        Err(source) => FetchError::Network {
+                            url: url.to_string(),
+                            source,
+                        },
+                    },
+                    Err(e) => e,
+                },
+                Err(source) => FetchError::Network {
+                    url: url.to_string(),
+                    source,
+                },
+            };
+
+            if !error.is_retryable() || attempt >= MAX_ATTEMPTS {
+                return Err(error);
             }
+
+            let delay = match &error {
+                FetchError::Throttled {
+                    retry_after: Some(wait),
+                    ..
+                } => {
+                    // Throttling applies to the whole host, so hold back every scraper
+                    self.limiter.pause_for(*wait).await;
+                    *wait
+                }
+                _ => backoff_delay(attempt),
+            };
+
+            tracing::warn!(
+                url,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                error = %error,
+                "Retrying request"
+            );
+
+            tokio::time::sleep(delay).await;
         }
+    }
+}
+
+fn trim_root(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
+fn classify_response(url: &str, response: Response) -> Result<Response, FetchError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
 
-        unreachable!("Loop should return before reaching this point")
+    let url = url.to_string();
+    Err(match status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => FetchError::NotFound { url },
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => FetchError::Throttled {
+            url,
+            status,
+            retry_after: parse_retry_after(&response),
+        },
+        _ => FetchError::Status { url, status },
+    })
+}
+
+/// Reads `Retry-After` as either delta-seconds or an HTTP date.
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds).min(BACKOFF_MAX));
     }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date
+        .signed_duration_since(chrono::Utc::now())
+        .to_std()
+        .ok()?;
+    Some(wait.min(BACKOFF_MAX))
 }
 
+/// Exponential backoff with full jitter.
+fn backoff_delay(attempt: u32) -> Duration {
+    let ceiling = BACKOFF_BASE
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(BACKOFF_MAX);
+    let jittered = rand::thread_rng().gen_range(0..=ceiling.as_millis() as u64);
+    Duration::from_millis(jittered)
+}
+
+/// The first `Blueprint'...'` reference in `text` that parses, normalised to
+/// the wrapped form.
 pub fn extract_blueprint(text: &str) -> Option<String> {
-    let re = Regex::new(r#"Blueprint'/Game/.*?'"#).ok()?;
-    re.find(text).map(|m| m.as_str().to_string())
+    let re = Regex::new(r#"Blueprint'/[^'\s]*'"#).ok()?;
+    let blueprint = re
+        .find_iter(text)
+        .find_map(|m| Blueprint::parse(m.as_str()).ok())?;
+    Some(blueprint.wrapped())
 }
 
 pub fn clean_name(name: &str) -> String {
@@ -79,33 +333,3 @@ pub fn clean_name(name: &str) -> String {
         .trim()
         .to_string()
 }
-
-pub fn extract_mod_name(blueprint: &str) -> String {
-    if blueprint.contains("/PrimalEarth/") {
-        "Ark".to_string()
-    } else if blueprint.contains("/ScorchedEarth/") {
-        "Scorched Earth".to_string()
-    } else if blueprint.contains("/Aberration/") {
-        "Aberration".to_string()
-    } else if blueprint.contains("/Extinction/") {
-        "Extinction".to_string()
-    } else if blueprint.contains("/Genesis/") {
-        "Genesis".to_string()
-    } else if blueprint.contains("/Gen2/") {
-        "Genesis 2".to_string()
-    } else if blueprint.contains("/Valguero/") {
-        "Valguero".to_string()
-    } else if blueprint.contains("/CrystalIsles/") {
-        "Crystal Isles".to_string()
-    } else if blueprint.contains("/Ragnarok/") {
-        "Ragnarok".to_string()
-    } else if blueprint.contains("/TheCenter/") {
-        "The Center".to_string()
-    } else if blueprint.contains("/LostIsland/") {
-        "Lost Island".to_string()
-    } else if blueprint.contains("/Fjordur/") {
-        "Fjordur".to_string()
-    } else {
-        "Unknown".to_string()
-    }
-}
@@ -0,0 +1,124 @@
+use super::common::{BASE_URL, DEFAULT_REQUESTS_PER_SECOND};
+use super::fixtures::FixtureMode;
+use crate::types::Edition;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "artis-arkdata-manager/",
+    env!("CARGO_PKG_VERSION"),
+    " (+https://github.com/jonxmitchell/artis-arkdata)"
+);
+
+/// The slowest request rate accepted, one request every 100 seconds.
+pub const MIN_REQUESTS_PER_SECOND: f32 = 0.01;
+
+/// Network settings shared by every scraper in a run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScraperConfig {
+    /// Wiki root, e.g. `https://ark.wiki.gg` or a local copy such as `http://localhost:8080`.
+    pub base_url: String,
+    /// Alternative roots tried in order when the base URL fails.
+    pub mirrors: Vec<String>,
+    /// HTTP or HTTPS proxy URL used for all requests.
+    pub proxy: Option<String>,
+    /// Path to an extra PEM root certificate to trust.
+    pub ca_certificate: Option<String>,
+    /// At least one second.
+    pub timeout_secs: u64,
+    pub user_agent: String,
+    /// At least [`MIN_REQUESTS_PER_SECOND`].
+    pub requests_per_second: f32,
+    /// Record fetched pages to, or replay them from, a fixture directory.
+    pub fixtures: FixtureMode,
+    /// Share of the previous scrape's entries a stage must reach before its
+    /// output is trusted; fewer suggests the wiki layout changed. Between 0
+    /// and 1.
+    pub min_entry_ratio: f32,
+    /// The game the wiki pages describe, recorded on entries that neither
+    /// their row, section nor mod assign to one.
+    pub edition: Option<Edition>,
+}
+
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            mirrors: Vec::new(),
+            proxy: None,
+            ca_certificate: None,
+            timeout_secs: 30,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            fixtures: FixtureMode::Off,
+            min_entry_ratio: 0.5,
+            edition: None,
+        }
+    }
+}
+
+impl ScraperConfig {
+    /// Rejects request rates the rate limiter can't turn into an interval
+    /// (zero, negative, not a number, or below [`MIN_REQUESTS_PER_SECOND`]),
+    /// a zero timeout, which fails every request, and entry ratios outside
+    /// 0 to 1.
+    pub fn validate(&self) -> Result<(), String> {
+        let rate = self.requests_per_second;
+        if !rate.is_finite() || rate < MIN_REQUESTS_PER_SECOND {
+            return Err(format!(
+                "Invalid request rate: {} per second, expected a number of at least {}",
+                rate, MIN_REQUESTS_PER_SECOND
+            ));
+        }
+        if self.timeout_secs == 0 {
+            return Err("Invalid timeout: expected at least 1 second".to_string());
+        }
+        let ratio = self.min_entry_ratio;
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(format!(
+                "Invalid minimum entry ratio: {}, expected a number from 0 to 1",
+                ratio
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_the_scraper_cannot_use_are_rejected() {
+        assert!(ScraperConfig::default().validate().is_ok());
+
+        for rate in [0.0, -1.0, f32::NAN, f32::INFINITY, 1e-40] {
+            let config = ScraperConfig {
+                requests_per_second: rate,
+                ..ScraperConfig::default()
+            };
+            assert!(config.validate().is_err(), "{}", rate);
+        }
+
+        let config = ScraperConfig {
+            timeout_secs: 0,
+            ..ScraperConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        for ratio in [-0.1, 1.5, f32::NAN, f32::INFINITY] {
+            let config = ScraperConfig {
+                min_entry_ratio: ratio,
+                ..ScraperConfig::default()
+            };
+            assert!(config.validate().is_err(), "{}", ratio);
+        }
+        for ratio in [0.0, 1.0] {
+            let config = ScraperConfig {
+                min_entry_ratio: ratio,
+                ..ScraperConfig::default()
+            };
+            assert!(config.validate().is_ok(), "{}", ratio);
+        }
+    }
+}
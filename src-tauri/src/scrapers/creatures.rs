@@ -1,15 +1,47 @@
+use super::columns::{matching_tables, Column};
+use super::layout::expect_at_least;
+use super::mods::UNKNOWN_MOD;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper};
+use super::rules::RowFields;
 use super::{common::*, progress::ScrapingProgress};
 use crate::types::Creature;
+use async_trait::async_trait;
 use scraper::{Html, Selector};
-use std::collections::HashMap;
-use tauri::Window;
-
-pub async fn scrape_creatures(
-    window: &Window,
-    creatures: &mut HashMap<String, Creature>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = ScraperClient::new();
-    let url = format!("{}/wiki/Creature_IDs", BASE_URL);
+
+const NAME: &str = "Creature";
+const ENTITY_ID: &str = "Entity ID";
+const BLUEPRINT: &str = "Blueprint Path";
+const EDITION: &str = "Edition";
+
+const COLUMNS: &[Column] = &[
+    Column::required(NAME, &["Name"]),
+    Column::required(ENTITY_ID, &["EntityID", "ID"]),
+    Column::required(BLUEPRINT, &["Blueprint"]),
+    Column::optional(EDITION, &["Game", "Availability"]),
+];
+
+pub struct CreatureScraper;
+
+#[async_trait]
+impl Scraper for CreatureScraper {
+    fn name(&self) -> &'static str {
+        "creatures"
+    }
+
+    fn category(&self) -> Category {
+        Category::Creatures
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_creatures(ctx, sink).await
+    }
+}
+
+pub async fn scrape_creatures(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+    let client = &ctx.client;
+
+    let url = client.url("/wiki/Creature_IDs");
     let html = client.fetch_page(&url).await?;
     let document = Html::parse_document(&html);
 
@@ -22,16 +54,19 @@ pub async fn scrape_creatures(
 
     // First pass: Count total rows across all tables
     let tables: Vec<_> = document.select(&table_selector).collect();
+    let tables = matching_tables(&tables, COLUMNS, &url);
+    expect_at_least(&url, "creature tables", tables.len(), 1)?;
     let total_rows = tables
         .iter()
-        .map(|table| table.select(&row_selector).count().saturating_sub(1)) // Subtract 1 for header
+        .map(|(table, _)| table.select(&row_selector).count().saturating_sub(1)) // Subtract 1 for header
         .sum::<usize>();
 
     // Second pass: Process the creatures
-    let mut current_mod = String::from("Ark");
+    let mut current_mod = UNKNOWN_MOD.to_string();
+    let mut current_header = String::new();
     let mut processed_rows = 0;
 
-    for table in &tables {
+    for (table, columns) in &tables {
         // Look for the preceding h3 header
         let mut current_element = table.prev_sibling();
         while let Some(element) = current_element {
@@ -40,7 +75,8 @@ pub async fn scrape_creatures(
                     if let Some(headline) = scraper::ElementRef::wrap(element)
                         .and_then(|el| el.select(&header_selector).next())
                     {
-                        current_mod = extract_mod_from_header(&headline.text().collect::<String>());
+                        current_header = headline.text().collect();
+                        current_mod = ctx.mods.from_header(&current_header);
                     }
                     break;
                 }
@@ -53,7 +89,7 @@ pub async fn scrape_creatures(
         for row in rows {
             let cells: Vec<_> = row.select(&cell_selector).collect();
 
-            if cells.len() >= 4 {
+            if columns.fits(&cells) {
                 processed_rows += 1;
                 let progress = (processed_rows as f32 / total_rows as f32) * 100.0;
 
@@ -65,10 +101,12 @@ pub async fn scrape_creatures(
                         processed_rows, total_rows, current_mod
                     ),
                 )
-                .emit(window);
+                .items(processed_rows)
+                .url(&url)
+                .emit(tracker);
 
-                // Get display name from the title attribute of the last link in the first cell
-                let display_name = if let Some(name_cell) = cells.first() {
+                // Get display name from the title attribute of the last link in the name cell
+                let display_name = if let Some(name_cell) = columns.cell(&cells, NAME) {
                     let links: Vec<_> = name_cell.select(&link_selector).collect();
                     if let Some(last_link) = links.last() {
                         if let Some(title) = last_link.value().attr("title") {
@@ -83,30 +121,50 @@ pub async fn scrape_creatures(
                     continue;
                 };
 
-                if !should_skip_creature(&display_name) {
-                    // Extract entity ID from fourth column
-                    let entity_id = clean_name(&cells[3].text().collect::<String>());
+                let blueprint = columns
+                    .cell(&cells, BLUEPRINT)
+                    .and_then(|cell| extract_blueprint(&cell.text().collect::<String>()));
 
-                    // Extract blueprint from last column
-                    let blueprint_text = cells
-                        .last()
-                        .map(|cell| cell.text().collect::<String>())
+                // Fall back to the blueprint path under headers the table doesn't know
+                let mod_id = match &blueprint {
+                    Some(blueprint) if current_mod == UNKNOWN_MOD => {
+                        ctx.mods.from_blueprint(blueprint)
+                    }
+                    _ => current_mod.clone(),
+                };
+
+                let row = RowFields {
+                    name: &display_name,
+                    blueprint: blueprint.as_deref(),
+                    mod_id: Some(&mod_id),
+                };
+
+                if let Some(rule) = ctx.rules.matching("creatures", &row) {
+                    sink.skip_by_rule(&display_name, rule, &url);
+                } else {
+                    let entity_id = columns
+                        .cell(&cells, ENTITY_ID)
+                        .map(|cell| clean_name(&cell.text().collect::<String>()))
                         .unwrap_or_default();
 
-                    if let Some(blueprint) = extract_blueprint(&blueprint_text) {
-                        // Use display name for the key generation
-                        let key = generate_unique_key(&display_name, &blueprint, &current_mod);
-
-                        creatures.insert(
-                            key.clone(),
-                            Creature {
-                                type_name: "creature".to_string(),
-                                name: display_name.clone(),
-                                mod_name: current_mod.clone(),
-                                entity_id,
-                                blueprint,
-                            },
-                        );
+                    // An edition column beats the section header
+                    let marker = columns
+                        .cell(&cells, EDITION)
+                        .map(|cell| cell.text().collect::<String>())
+                        .unwrap_or_else(|| current_header.clone());
+                    let editions = ctx.mods.editions(&marker, &mod_id, ctx.edition);
+
+                    if let Some(blueprint) = blueprint {
+                        sink.insert(Creature {
+                            type_name: "creature".to_string(),
+                            name: display_name.clone(),
+                            mod_id,
+                            editions,
+                            entity_id,
+                            blueprint,
+                        });
+                    } else {
+                        sink.skip(&display_name, "no blueprint found", &url);
                     }
                 }
             }
@@ -117,48 +175,9 @@ pub async fn scrape_creatures(
     ScrapingProgress::new(
         "creatures",
         100.0,
-        &format!("Completed processing {} creatures", creatures.len()),
+        &format!("Completed processing {} creatures", sink.len()),
     )
-    .emit(window);
+    .emit(tracker);
 
     Ok(())
 }
-
-fn generate_unique_key(name: &str, _blueprint: &str, mod_name: &str) -> String {
-    format!(
-        "{}_{}",
-        mod_name.replace(" ", "_"),
-        name.replace(" ", "_").replace("-", "_")
-    )
-}
-
-fn extract_mod_from_header(header: &str) -> String {
-    if header.contains("Scorched Earth") {
-        "Scorched Earth".to_string()
-    } else if header.contains("Aberration") {
-        "Aberration".to_string()
-    } else if header.contains("Extinction") {
-        "Extinction".to_string()
-    } else if header.contains("Genesis") {
-        "Genesis".to_string()
-    } else {
-        "Ark".to_string()
-    }
-}
-
-fn should_skip_creature(name: &str) -> bool {
-    let skip_patterns = [
-        "Alpha",
-        "VR",
-        "Malfunctioned",
-        "Retrieve",
-        "Escort",
-        "Minion",
-        "Race",
-        "Sport",
-        "Summoned",
-        "Hunt",
-        "Mashup",
-    ];
-    skip_patterns.iter().any(|pattern| name.contains(pattern))
-}
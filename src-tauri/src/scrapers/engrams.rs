@@ -1,23 +1,70 @@
 // src-tauri/src/scrapers/engrams.rs
+use super::columns::{matching_tables, Column};
+use super::layout::expect_at_least;
+use super::mods::ModMap;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper};
+use super::revisions::{fetch_revisions, fingerprint};
+use super::rules::{RowFields, SkipRules};
 use super::{common::*, progress::ScrapingProgress};
-use crate::types::Engram;
+use crate::types::{Edition, Engram};
+use async_trait::async_trait;
 use futures::stream::{self, StreamExt};
 use scraper::{Html, Selector};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tauri::Window;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub async fn scrape_engrams(
-    window: &Window,
-    engrams: &mut HashMap<String, Engram>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = Arc::new(ScraperClient::new());
+const ENGRAM: &str = "Engram";
+const CLASS_NAME: &str = "Class Name";
+
+const LIST_COLUMNS: &[Column] = &[Column::required(ENGRAM, &["Name"])];
+
+const CLASS_NAME_COLUMNS: &[Column] = &[
+    Column::required(ENGRAM, &["Name"]),
+    Column::required(CLASS_NAME, &["Class", "ClassName"]),
+];
+
+pub struct EngramScraper;
+
+#[async_trait]
+impl Scraper for EngramScraper {
+    fn name(&self) -> &'static str {
+        "engrams"
+    }
+
+    fn category(&self) -> Category {
+        Category::Engrams
+    }
+
+    /// Two list pages plus one page per engram, several hundred in all,
+    /// fetched a few at a time.
+    fn weight(&self) -> f32 {
+        40.0
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_engrams(ctx, sink).await
+    }
+
+    async fn retry_units(
+        &self,
+        ctx: &ScrapeContext,
+        units: &[String],
+        sink: &mut ScrapeSink,
+    ) -> ScrapeResult<()> {
+        let class_names = fetch_class_names(&ctx.client).await?;
+        scrape_pages(ctx, &class_names, units.to_vec(), sink).await;
+        Ok(())
+    }
+}
+
+pub async fn scrape_engrams(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let client = &ctx.client;
 
     // First, fetch the class names
-    let class_names = fetch_class_names(&client).await?;
+    let class_names = fetch_class_names(client).await?;
 
     // Then fetch the regular engram data
-    let url = format!("{}/wiki/Engrams", BASE_URL);
+    let url = client.url("/wiki/Engrams");
     let html = client.fetch_page(&url).await?;
 
     // Process HTML in a separate scope to ensure it doesn't cross await boundaries
@@ -28,16 +75,20 @@ pub async fn scrape_engrams(
         let cell_selector = Selector::parse("td").unwrap();
         let link_selector = Selector::parse("a").unwrap();
 
+        let tables: Vec<_> = document.select(&table_selector).collect();
+        let tables = matching_tables(&tables, LIST_COLUMNS, &url);
+        expect_at_least(&url, "engram tables", tables.len(), 1)?;
+
         let mut links = Vec::new();
-        for table in document.select(&table_selector) {
+        for (table, columns) in tables {
             for row in table.select(&row_selector).skip(1) {
-                if let Some(link) = row
-                    .select(&cell_selector)
-                    .next()
+                let cells: Vec<_> = row.select(&cell_selector).collect();
+                if let Some(link) = columns
+                    .cell(&cells, ENGRAM)
                     .and_then(|cell| cell.select(&link_selector).next())
                 {
                     if let Some(href) = link.value().attr("href") {
-                        links.push(format!("{}{}", BASE_URL, href));
+                        links.push(client.url(href));
                     }
                 }
             }
@@ -45,46 +96,121 @@ pub async fn scrape_engrams(
         links
     };
 
-    let total_engrams = engram_links.len();
-    let window = Arc::new(window.clone());
+    // Forget pages that are no longer linked from the wiki
+    ctx.revisions.retain(&engram_links);
+    scrape_pages(ctx, &class_names, engram_links, sink).await;
+
+    Ok(())
+}
+
+/// Fetches the engram pages at `links`, a few at a time. Pages that fail are
+/// recorded as failed units and don't stop the others.
+async fn scrape_pages(
+    ctx: &ScrapeContext,
+    class_names: &HashMap<String, String>,
+    links: Vec<String>,
+    sink: &mut ScrapeSink,
+) {
+    let client = &ctx.client;
+    let total_engrams = links.len();
+    let tracker = &ctx.progress;
+    let checkpoint = &ctx.checkpoint;
+    let rules = &ctx.rules;
+    let mods = &ctx.mods;
+    let edition = ctx.edition;
+    let cache = &ctx.revisions;
+
+    // Everything besides the page that shapes its output; a cached page
+    // parsed with different inputs is stale
+    let sorted_class_names: BTreeMap<_, _> = class_names.iter().collect();
+    let rule_list: Vec<_> = rules.rules().collect();
+    let inputs = fingerprint(&(rule_list, sorted_class_names, mods, edition));
+
+    // Pages still at the revision cached last time are not fetched again
+    let revisions = if cache.is_enabled() {
+        fetch_revisions(client, &links).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to look up engram revisions; fetching every page");
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+    let revisions = &revisions;
+    let unchanged = AtomicUsize::new(0);
+    let unchanged = &unchanged;
 
     // Process engrams in parallel with controlled concurrency
-    let mut stream = stream::iter(engram_links.into_iter().enumerate())
-        .map(|(idx, url): (usize, String)| {
-            let client = Arc::clone(&client);
-            let window = Arc::clone(&window);
-            let class_names = class_names.clone();
+    let mut stream = stream::iter(links)
+        .map(|url: String| {
+            let client = client.clone();
 
             async move {
-                let progress = (idx as f32 / total_engrams as f32) * 100.0;
-                ScrapingProgress::new(
-                    "engrams",
-                    progress,
-                    &format!("Processing engram {} of {}", idx + 1, total_engrams),
-                )
-                .emit(&window);
+                if let Some(restored) = checkpoint.completed_unit("engrams", &url) {
+                    return (url, restored);
+                }
 
-                match scrape_single_engram(&client, &url, &class_names).await {
-                    Ok(Some((key, engram))) => Some((key, engram)),
-                    _ => None,
+                let revision = revisions.get(&url).copied();
+                if let Some(cached) = revision.and_then(|rev| cache.get(&url, rev, inputs)) {
+                    unchanged.fetch_add(1, Ordering::Relaxed);
+                    return (url, cached);
                 }
+
+                let mut page_sink = ScrapeSink::new();
+                match scrape_single_engram(
+                    &client,
+                    &url,
+                    class_names,
+                    rules,
+                    mods,
+                    edition,
+                    &mut page_sink,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        checkpoint.complete_unit("engrams", &url, &page_sink);
+                        if let Some(revision) = revision {
+                            cache.put("engrams", &url, revision, inputs, &page_sink);
+                        }
+                    }
+                    // Not checkpointed, so a resumed run tries the page again
+                    Err(e) => {
+                        page_sink.fetch_failed(&url, &e);
+                        page_sink.unit_failed(&url);
+                    }
+                }
+                (url, page_sink)
             }
         })
         .buffer_unordered(5);
 
-    while let Some(result) = stream.next().await {
-        if let Some((key, engram)) = result {
-            engrams.insert(key, engram);
-        }
+    let mut processed = 0;
+    while let Some((url, page_sink)) = stream.next().await {
+        sink.absorb(page_sink);
+        processed += 1;
+
+        ScrapingProgress::new(
+            "engrams",
+            (processed as f32 / total_engrams as f32) * 100.0,
+            &format!("Processed engram {} of {}", processed, total_engrams),
+        )
+        .items(processed)
+        .errors(sink.failed_fetches().len())
+        .url(&url)
+        .emit(tracker);
     }
 
-    Ok(())
+    tracing::info!(
+        pages = total_engrams,
+        unchanged = unchanged.load(Ordering::Relaxed),
+        "Reused engram pages unchanged since the last scrape"
+    );
 }
 
 async fn fetch_class_names(
     client: &ScraperClient,
 ) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
-    let url = format!("{}/wiki/Engram_class_names", BASE_URL);
+    let url = client.url("/wiki/Engram_class_names");
     let html = client.fetch_page(&url).await?;
     let document = Html::parse_document(&html);
 
@@ -95,23 +221,26 @@ async fn fetch_class_names(
 
     let mut class_names = HashMap::new();
 
-    if let Some(table) = document.select(&table_selector).next() {
+    let tables: Vec<_> = document.select(&table_selector).collect();
+    let tables = matching_tables(&tables, CLASS_NAME_COLUMNS, &url);
+    expect_at_least(&url, "class name tables", tables.len(), 1)?;
+
+    if let Some((table, columns)) = tables.first() {
         for row in table.select(&row_selector).skip(1) {
             // Skip header row
             let cells: Vec<_> = row.select(&cell_selector).collect();
-            if cells.len() >= 2 {
-                if let Some(name_cell) = cells.get(0) {
-                    let name = if let Some(link) = name_cell.select(&link_selector).next() {
-                        clean_name(&link.text().collect::<String>())
-                    } else {
-                        clean_name(&name_cell.text().collect::<String>())
-                    };
-
-                    if let Some(class_cell) = cells.get(1) {
-                        let class_name = clean_name(&class_cell.text().collect::<String>());
-                        class_names.insert(name, class_name);
-                    }
-                }
+            if let (Some(name_cell), Some(class_cell)) = (
+                columns.cell(&cells, ENGRAM),
+                columns.cell(&cells, CLASS_NAME),
+            ) {
+                let name = if let Some(link) = name_cell.select(&link_selector).next() {
+                    clean_name(&link.text().collect::<String>())
+                } else {
+                    clean_name(&name_cell.text().collect::<String>())
+                };
+
+                let class_name = clean_name(&class_cell.text().collect::<String>());
+                class_names.insert(name, class_name);
             }
         }
     }
@@ -123,50 +252,60 @@ async fn scrape_single_engram(
     client: &ScraperClient,
     url: &str,
     class_names: &HashMap<String, String>,
-) -> Result<Option<(String, Engram)>, Box<dyn std::error::Error + Send + Sync>> {
+    rules: &SkipRules,
+    mods: &ModMap,
+    edition: Option<Edition>,
+    sink: &mut ScrapeSink,
+) -> Result<(), FetchError> {
     let html = client.fetch_page(url).await?;
 
-    // Process HTML in a separate scope
-    let result = {
-        let document = Html::parse_document(&html);
-        let name_selector = Selector::parse("h1").unwrap();
-        let command_selector = Selector::parse("code.copy-clipboard").unwrap();
-
-        if let Some(name_elem) = document.select(&name_selector).next() {
-            let name = clean_name(&name_elem.text().collect::<String>());
-
-            if !should_skip_engram(&name) {
-                let blueprint = document
-                    .select(&command_selector)
-                    .find(|elem| elem.text().collect::<String>().contains("Blueprint"))
-                    .and_then(|elem| extract_blueprint(&elem.text().collect::<String>()))
-                    .unwrap_or_else(|| "Unknown".to_string());
-
-                let key = name.replace(" ", "_");
-                let class_name = class_names
-                    .get(&name)
-                    .cloned()
-                    .unwrap_or_else(|| format!("EngramEntry_{}_C", key));
-
-                return Ok(Some((
-                    key,
-                    Engram {
-                        type_name: "engram".to_string(),
-                        name,
-                        mod_name: extract_mod_name(&blueprint),
-                        blueprint,
-                        class_name,
-                    },
-                )));
-            }
+    let document = Html::parse_document(&html);
+    let name_selector = Selector::parse("h1").unwrap();
+    let command_selector = Selector::parse("code.copy-clipboard").unwrap();
+
+    let name = match document.select(&name_selector).next() {
+        Some(name_elem) => clean_name(&name_elem.text().collect::<String>()),
+        None => {
+            sink.skip(url, "page has no title", url);
+            return Ok(());
         }
-        Ok(None)
     };
 
-    result
-}
+    let blueprint = document
+        .select(&command_selector)
+        .find(|elem| elem.text().collect::<String>().contains("Blueprint"))
+        .and_then(|elem| extract_blueprint(&elem.text().collect::<String>()));
+    let mod_id = blueprint.as_deref().map(|bp| mods.from_blueprint(bp));
 
-fn should_skip_engram(name: &str) -> bool {
-    let skip_patterns = ["Platform_Cart"];
-    skip_patterns.iter().any(|pattern| name.contains(pattern))
+    let row = RowFields {
+        name: &name,
+        blueprint: blueprint.as_deref(),
+        mod_id: mod_id.as_deref(),
+    };
+    if let Some(rule) = rules.matching("engrams", &row) {
+        sink.skip_by_rule(&name, rule, url);
+        return Ok(());
+    }
+
+    let blueprint = blueprint.unwrap_or_else(|| "Unknown".to_string());
+
+    let class_name = class_names
+        .get(&name)
+        .cloned()
+        .unwrap_or_else(|| format!("EngramEntry_{}_C", name.replace(' ', "_")));
+
+    // Engram pages have no edition column, so go by the mod
+    let mod_id = mods.from_blueprint(&blueprint);
+    let editions = mods.editions("", &mod_id, edition);
+
+    sink.insert(Engram {
+        type_name: "engram".to_string(),
+        name,
+        mod_id,
+        editions,
+        blueprint,
+        class_name,
+    });
+
+    Ok(())
 }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether fetched pages are recorded to, or served from, a fixture directory.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum FixtureMode {
+    /// Always talk to the network.
+    Off,
+    /// Fetch live and save every page under `dir`.
+    Record { dir: PathBuf },
+    /// Never touch the network; serve pages from `dir`.
+    Replay { dir: PathBuf },
+}
+
+impl Default for FixtureMode {
+    fn default() -> Self {
+        FixtureMode::Off
+    }
+}
+
+/// File a page is stored under, derived from its path relative to the wiki
+/// root so recordings work against any base URL or mirror.
+pub fn fixture_path(dir: &Path, relative_url: &str) -> PathBuf {
+    let name: String = relative_url
+        .trim_start_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    dir.join(format!("{}.html", name))
+}
+
+pub fn read_fixture(dir: &Path, relative_url: &str) -> io::Result<String> {
+    fs::read_to_string(fixture_path(dir, relative_url))
+}
+
+pub fn write_fixture(dir: &Path, relative_url: &str, body: &str) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(fixture_path(dir, relative_url), body)
+}
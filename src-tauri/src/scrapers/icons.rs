@@ -1,21 +1,41 @@
 // src-tauri/src/scrapers/icons.rs
 use super::progress::ScrapingProgress;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper};
 use crate::types::Icon;
-use std::collections::HashMap;
+use async_trait::async_trait;
 use std::fs;
-use tauri::{Manager, Window}; // Added Manager trait
-
-pub async fn scrape_icons(
-    window: &Window,
-    icons: &mut HashMap<String, Icon>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let app_handle = window.app_handle();
-    let resource_path = app_handle
-        .path_resolver()
-        .resolve_resource("resources/icons.txt")
+
+pub struct IconScraper;
+
+#[async_trait]
+impl Scraper for IconScraper {
+    fn name(&self) -> &'static str {
+        "icons"
+    }
+
+    fn category(&self) -> Category {
+        Category::Icons
+    }
+
+    /// Reads a bundled file instead of fetching pages.
+    fn weight(&self) -> f32 {
+        0.1
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_icons(ctx, sink).await
+    }
+}
+
+pub async fn scrape_icons(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+
+    let resource_path = ctx
+        .icons_file
+        .as_ref()
         .ok_or("Failed to find icons.txt resource")?;
 
-    ScrapingProgress::new("icons", 0.0, "Starting icon data collection...").emit(window);
+    ScrapingProgress::new("icons", 0.0, "Starting icon data collection...").emit(tracker);
 
     let content = fs::read_to_string(resource_path)?;
     let lines: Vec<&str> = content.lines().collect();
@@ -29,21 +49,17 @@ pub async fn scrape_icons(
             progress,
             &format!("Processing icon {} of {}", index + 1, total_lines),
         )
-        .emit(window);
+        .items(index + 1)
+        .emit(tracker);
 
         // Extract the icon name from the path
-        if let Some(name) = line.split('/').last() {
+        if let Some(name) = line.rsplit('/').next() {
             if let Some(icon_name) = name.split('.').next() {
-                let key = icon_name.to_string();
-
-                icons.insert(
-                    key.clone(),
-                    Icon {
-                        type_name: "icon".to_string(),
-                        name: icon_name.to_string(),
-                        path: line.to_string(),
-                    },
-                );
+                sink.insert(Icon {
+                    type_name: "icon".to_string(),
+                    name: icon_name.to_string(),
+                    path: line.to_string(),
+                });
             }
         }
     }
@@ -51,9 +67,9 @@ pub async fn scrape_icons(
     ScrapingProgress::new(
         "icons",
         100.0,
-        &format!("Completed processing {} icons", icons.len()),
+        &format!("Completed processing {} icons", sink.len()),
     )
-    .emit(window);
+    .emit(tracker);
 
     Ok(())
 }
@@ -1,47 +1,166 @@
+use super::columns::{matching_tables, Column};
+use super::layout::expect_at_least;
+use super::registry::{Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper, UnitsFailed};
+use super::rules::RowFields;
 use super::{common::*, progress::ScrapingProgress};
 use crate::types::Item;
+use async_trait::async_trait;
+use reqwest::Url;
 use scraper::{ElementRef, Html, Selector};
-use std::collections::HashMap;
-use std::time::Duration;
-use tauri::Window;
-
-const SECTIONS: &[&str] = &[
-    "Resources",
-    "Tools",
-    "Armor",
-    "Saddles",
-    "Structures",
-    "Vehicles",
-    "Dye",
-    "Consumables",
-    "Recipes",
-    "Eggs",
-    "Farming",
-    "Seeds",
-    "Weapons", // Changed from "Weapons and Attachments"
-    "Ammunition",
-    "Skins",
-    "Chibi Pets",
-    "Artifacts",
-    "Trophy", // Changed from "Trophies"
+use serde::Deserialize;
+
+const NAME: &str = "Name";
+const CATEGORY: &str = "Category";
+const CLASS_NAME: &str = "Class Name";
+const BLUEPRINT: &str = "Blueprint Path";
+const EDITION: &str = "Edition";
+
+const COLUMNS: &[Column] = &[
+    Column::required(NAME, &["Item"]),
+    Column::optional(CATEGORY, &["Type"]),
+    Column::optional(CLASS_NAME, &["Class", "ClassName"]),
+    Column::required(BLUEPRINT, &["Blueprint"]),
+    Column::optional(EDITION, &["Game", "Availability"]),
 ];
 
-fn format_section_url(section: &str) -> String {
-    // Special cases for sections with different URL patterns
-    match section {
-        "Weapons" => format!("{}/wiki/Item_IDs/Weapons", BASE_URL),
-        "Trophy" => format!("{}/wiki/Item_IDs/Trophy", BASE_URL),
-        "Chibi Pets" => format!("{}/wiki/Item_IDs/Chibi_Pets", BASE_URL),
-        _ => {
-            let formatted = section
-                .replace(' ', "_")
-                .replace('&', "and")
-                .replace("_Pets", "-Pets");
-            format!("{}/wiki/Item_IDs/{}", BASE_URL, formatted)
+/// Title prefix shared by the per-section item ID subpages.
+const SECTION_PREFIX: &str = "Item_IDs/";
+
+/// One `Item_IDs/…` subpage, listing a group of items.
+struct Section {
+    name: String,
+    url: String,
+}
+
+/// The section a page title such as `Item IDs/Chibi Pets` or
+/// `Item_IDs/Chibi_Pets` names. Deeper subpages and translations such as
+/// `Item IDs/de` are not sections.
+pub(super) fn section_name(title: &str) -> Option<String> {
+    let name = title.replace(' ', "_");
+    let name = name.strip_prefix(SECTION_PREFIX)?.replace('_', " ");
+    if name.is_empty() || name.contains('/') || is_language_code(&name) {
+        return None;
+    }
+    Some(name)
+}
+
+/// Translation subpages are named by language code, e.g. `de` or `zh-hans`,
+/// while sections are capitalised.
+fn is_language_code(name: &str) -> bool {
+    let language = name.split('-').next().unwrap_or(name);
+    (2..=3).contains(&language.len()) && name.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+}
+
+impl Section {
+    fn from_title(client: &ScraperClient, title: &str) -> Option<Self> {
+        let name = section_name(title)?;
+        let title = title.replace(' ', "_");
+
+        Some(Self {
+            name,
+            url: client.url(&format!("/wiki/{}", title)),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct AllPagesResponse {
+    #[serde(rename = "continue")]
+    next: Option<AllPagesContinue>,
+    #[serde(default)]
+    query: AllPagesQuery,
+}
+
+#[derive(Deserialize, Default)]
+struct AllPagesQuery {
+    allpages: Vec<PageTitle>,
+}
+
+#[derive(Deserialize)]
+struct PageTitle {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct AllPagesContinue {
+    apcontinue: String,
+}
+
+/// Finds the item ID subpages on the wiki, so new sections are picked up
+/// without a code change. Uses the MediaWiki page listing, falling back to
+/// the links on the `Item_IDs` index page.
+async fn discover_sections(client: &ScraperClient) -> ScrapeResult<Vec<Section>> {
+    let titles = match list_subpages(client).await {
+        Ok(titles) if !titles.is_empty() => titles,
+        Ok(_) => index_subpages(client).await?,
+        Err(e) => {
+            tracing::warn!(error = %e, "Listing item subpages failed; reading the index page");
+            index_subpages(client).await?
+        }
+    };
+
+    let mut sections: Vec<Section> = titles
+        .iter()
+        .filter_map(|title| Section::from_title(client, title))
+        .collect();
+    sections.sort_by(|a, b| a.name.cmp(&b.name));
+    sections.dedup_by(|a, b| a.name == b.name);
+
+    expect_at_least(
+        &client.url("/wiki/Item_IDs"),
+        "item sections",
+        sections.len(),
+        1,
+    )?;
+    Ok(sections)
+}
+
+async fn list_subpages(client: &ScraperClient) -> ScrapeResult<Vec<String>> {
+    let mut titles = Vec::new();
+    let mut next: Option<String> = None;
+
+    loop {
+        let mut params = vec![
+            ("action", "query"),
+            ("list", "allpages"),
+            ("apprefix", SECTION_PREFIX),
+            ("apnamespace", "0"),
+            // Old section names redirect to their current page
+            ("apfilterredir", "nonredirects"),
+            ("aplimit", "max"),
+            ("format", "json"),
+        ];
+        if let Some(next) = &next {
+            params.push(("apcontinue", next));
+        }
+
+        let url = Url::parse_with_params(&client.url("/api.php"), &params)?;
+        let response: AllPagesResponse =
+            serde_json::from_str(&client.fetch_page(url.as_str()).await?)?;
+        titles.extend(response.query.allpages.into_iter().map(|page| page.title));
+
+        match response.next {
+            Some(cont) => next = Some(cont.apcontinue),
+            None => return Ok(titles),
         }
     }
 }
 
+async fn index_subpages(client: &ScraperClient) -> ScrapeResult<Vec<String>> {
+    let html = client.fetch_page(&client.url("/wiki/Item_IDs")).await?;
+    let document = Html::parse_document(&html);
+    let link_selector = Selector::parse("a[href]").unwrap();
+    let link_prefix = format!("/wiki/{}", SECTION_PREFIX);
+
+    Ok(document
+        .select(&link_selector)
+        .filter_map(|link| link.value().attr("href"))
+        .filter_map(|href| href.split('#').next())
+        .filter(|href| href.starts_with(&link_prefix))
+        .map(|href| href.trim_start_matches("/wiki/").to_string())
+        .collect())
+}
+
 fn extract_name_from_cell(cell: &ElementRef) -> Option<String> {
     let link_selector = Selector::parse("a").unwrap();
 
@@ -91,151 +210,86 @@ fn extract_class_name_from_cell(cell: &ElementRef) -> Option<String> {
     }
 }
 
-pub async fn scrape_items(
-    window: &Window,
-    items: &mut HashMap<String, Item>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = ScraperClient::new();
+pub struct ItemScraper;
+
+#[async_trait]
+impl Scraper for ItemScraper {
+    fn name(&self) -> &'static str {
+        "items"
+    }
+
+    fn category(&self) -> Category {
+        Category::Items
+    }
+
+    /// One page per item ID section, around two dozen.
+    fn weight(&self) -> f32 {
+        8.0
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        scrape_items(ctx, sink).await
+    }
+    async fn retry_units(
+        &self,
+        ctx: &ScrapeContext,
+        units: &[String],
+        sink: &mut ScrapeSink,
+    ) -> ScrapeResult<()> {
+        retry_sections(ctx, units, sink).await
+    }
+}
+
+/// What one section yielded.
+struct SectionResult {
+    sink: ScrapeSink,
+    items: usize,
+    /// Rows without a blueprint.
+    failed_items: usize,
+}
+
+pub async fn scrape_items(ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+    let client = &ctx.client;
 
-    ScrapingProgress::new("items", 0.0, "Starting item data collection...").emit(window);
+    ScrapingProgress::new("items", 0.0, "Starting item data collection...").emit(tracker);
 
     let mut processed_items = 0;
     let mut failed_items = 0;
     let mut failed_sections = Vec::new();
     let mut successful_sections = Vec::new();
 
-    for (section_idx, &section) in SECTIONS.iter().enumerate() {
-        let progress = (section_idx as f32 / SECTIONS.len() as f32) * 100.0;
+    let sections = discover_sections(client).await?;
+    sink.set_sections(sections.iter().map(|s| s.name.clone()).collect());
+
+    for (section_idx, section) in sections.iter().enumerate() {
+        let progress = (section_idx as f32 / sections.len() as f32) * 100.0;
 
         ScrapingProgress::new(
             "items",
             progress,
-            &format!("Processing section: {}", section),
+            &format!("Processing section: {}", section.name),
         )
-        .emit(window);
-
-        let mut urls_to_try = vec![format_section_url(section)];
-
-        // Add alternate URLs for problematic sections
-        match section {
-            "Trophy" => {
-                urls_to_try.push(format!("{}/wiki/Item_IDs/Trophies", BASE_URL));
-            }
-            "Weapons" => {
-                urls_to_try.push(format!("{}/wiki/Item_IDs/Weapon", BASE_URL));
-            }
-            _ => {}
+        .items(processed_items)
+        .errors(failed_sections.len())
+        .url(&section.url)
+        .emit(tracker);
+
+        if let Some(restored) = ctx.checkpoint.completed_unit("items", &section.name) {
+            processed_items += restored.len();
+            successful_sections.push(section.name.as_str());
+            sink.absorb(restored);
+            continue;
         }
 
-        let mut section_success = false;
-        let mut section_items = 0;
-
-        for url in &urls_to_try {
-            println!("\nTrying URL for section {}: {}", section, url);
-
-            match client.fetch_page(url).await {
-                Ok(content) => {
-                    let document = Html::parse_document(&content);
-                    let table_selector = Selector::parse("table.wikitable").unwrap();
-                    let row_selector = Selector::parse("tr").unwrap();
-                    let cell_selector = Selector::parse("td").unwrap();
-
-                    let tables: Vec<_> = document.select(&table_selector).collect();
-                    println!("Found {} tables in section {}", tables.len(), section);
+        let result = scrape_section(ctx, section).await?;
+        processed_items += result.items;
+        failed_items += result.failed_items;
 
-                    if tables.is_empty() {
-                        println!("No tables found for URL: {}", url);
-                        continue;
-                    }
-
-                    for (table_idx, table) in tables.iter().enumerate() {
-                        let rows: Vec<_> = table.select(&row_selector).collect();
-                        println!("Table {} has {} rows", table_idx + 1, rows.len());
-
-                        for row in rows.iter().skip(1) {
-                            let cells: Vec<_> = row.select(&cell_selector).collect();
-
-                            if cells.len() >= 5 {
-                                if let Some(name_cell) = cells.first() {
-                                    if let Some(name) = extract_name_from_cell(name_cell) {
-                                        if !should_skip_item(&name) {
-                                            let type_name = cells
-                                                .get(1)
-                                                .and_then(|cell| extract_type_from_cell(cell))
-                                                .unwrap_or_else(|| section.to_string());
-
-                                            let class_name = cells
-                                                .get(4)
-                                                .and_then(|cell| extract_class_name_from_cell(cell))
-                                                .unwrap_or_else(|| "Unknown".to_string());
-
-                                            if let Some(blueprint_cell) = cells.last() {
-                                                let blueprint_text =
-                                                    blueprint_cell.text().collect::<String>();
-
-                                                match extract_blueprint(&blueprint_text) {
-                                                    Some(blueprint) => {
-                                                        let key = name.replace(" ", "_");
-                                                        let mod_name = extract_mod_name(&blueprint);
-
-                                                        println!("Processing item: {}", name);
-                                                        println!("  Type: {}", type_name);
-                                                        println!("  Class: {}", class_name);
-                                                        println!("  Blueprint: {}", blueprint);
-                                                        println!("  Mod: {}", mod_name);
-
-                                                        items.insert(
-                                                            key,
-                                                            Item {
-                                                                type_name,
-                                                                name,
-                                                                mod_name,
-                                                                class_name,
-                                                                blueprint,
-                                                            },
-                                                        );
-
-                                                        processed_items += 1;
-                                                        section_items += 1;
-                                                    }
-                                                    None => {
-                                                        println!(
-                                                            "Failed to extract blueprint for: {}",
-                                                            name
-                                                        );
-                                                        failed_items += 1;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    if section_items > 0 {
-                        section_success = true;
-                        break; // Successfully processed this section, move to next
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to fetch URL {} for section {}: {}", url, section, e);
-                }
-            }
-
-            tokio::time::sleep(Duration::from_millis(500)).await;
-        }
-
-        if section_success {
-            successful_sections.push(section);
-            println!(
-                "Successfully processed {} items from section {}",
-                section_items, section
-            );
+        if record_section(ctx, section, result, sink) {
+            successful_sections.push(section.name.as_str());
         } else {
-            failed_sections.push(section);
-            println!("Failed to process section: {}", section);
+            failed_sections.push(section.name.as_str());
         }
     }
 
@@ -255,24 +309,227 @@ pub async fn scrape_items(
         }
     );
 
-    ScrapingProgress::new("items", 100.0, &completion_message).emit(window);
+    ScrapingProgress::new("items", 100.0, &completion_message).emit(tracker);
 
-    println!("\n{}", completion_message);
-    println!("Items in collection: {}", items.len());
+    tracing::info!(
+        processed = processed_items,
+        failed = failed_items,
+        collected = sink.len(),
+        "Finished items"
+    );
 
-    if items.is_empty() {
-        Err("No items were successfully scraped".into())
+    if sink.is_empty() {
+        Err(UnitsFailed("No items were successfully scraped".to_string()).into())
     } else {
         Ok(())
     }
 }
 
-fn should_skip_item(name: &str) -> bool {
-    let skip_patterns = ["undefined", "null", "(alt)", "Beer_Jar_", "Platform_Cart"];
+/// Scrapes the named sections again, typically the ones a previous run
+/// recorded as failed.
+async fn retry_sections(
+    ctx: &ScrapeContext,
+    names: &[String],
+    sink: &mut ScrapeSink,
+) -> ScrapeResult<()> {
+    let tracker = &ctx.progress;
+    let client = &ctx.client;
+
+    for (idx, name) in names.iter().enumerate() {
+        let section = match Section::from_title(client, &format!("{}{}", SECTION_PREFIX, name)) {
+            Some(section) => section,
+            None => {
+                tracing::warn!(section = name.as_str(), "Not an item section; skipping");
+                continue;
+            }
+        };
+
+        ScrapingProgress::new(
+            "items",
+            (idx as f32 / names.len() as f32) * 100.0,
+            &format!("Retrying section: {}", section.name),
+        )
+        .items(sink.len())
+        .errors(sink.failed_units().len())
+        .url(&section.url)
+        .emit(tracker);
+
+        let result = scrape_section(ctx, &section).await?;
+        record_section(ctx, &section, result, sink);
+    }
+
+    tracing::info!(
+        retried = names.len(),
+        failed = sink.failed_units().len(),
+        collected = sink.len(),
+        "Retried item sections"
+    );
+    Ok(())
+}
 
-    let result = skip_patterns.iter().any(|pattern| name.contains(pattern));
-    if result {
-        println!("Skipping item due to pattern match: {}", name);
+/// Adds a section's rows to `sink` and checkpoints it, or records it as
+/// failed when it yielded no items. Returns whether it succeeded.
+fn record_section(
+    ctx: &ScrapeContext,
+    section: &Section,
+    result: SectionResult,
+    sink: &mut ScrapeSink,
+) -> bool {
+    let name = section.name.as_str();
+    if result.items > 0 {
+        ctx.checkpoint.complete_unit("items", name, &result.sink);
+        sink.absorb(result.sink);
+        tracing::info!(section = name, items = result.items, "Processed section");
+        true
+    } else {
+        sink.absorb(result.sink);
+        sink.unit_failed(name);
+        tracing::warn!(section = name, "Failed to process section");
+        false
+    }
+}
+
+/// Reads one section's tables. A failed fetch is recorded in the result
+/// rather than returned, so the remaining sections still run.
+async fn scrape_section(ctx: &ScrapeContext, section: &Section) -> ScrapeResult<SectionResult> {
+    let Section { name: section, url } = section;
+    let section = section.as_str();
+
+    let mut result = SectionResult {
+        sink: ScrapeSink::new(),
+        items: 0,
+        failed_items: 0,
+    };
+    let section_sink = &mut result.sink;
+
+    tracing::debug!(section, url = url.as_str(), "Fetching section");
+
+    let content = match ctx.client.fetch_page(url).await {
+        Ok(content) => content,
+        Err(e) => {
+            section_sink.fetch_failed(url, &e);
+            return Ok(result);
+        }
+    };
+
+    let document = Html::parse_document(&content);
+    let table_selector = Selector::parse("table.wikitable").unwrap();
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("td").unwrap();
+
+    let tables: Vec<_> = document.select(&table_selector).collect();
+    tracing::debug!(section, tables = tables.len(), "Found tables");
+
+    let item_tables = matching_tables(&tables, COLUMNS, url);
+    if tables.is_empty() {
+        tracing::warn!(section, url = url.as_str(), "No tables found");
+    } else {
+        expect_at_least(url, "item tables", item_tables.len(), 1)?;
+    }
+
+    for (table_idx, (table, columns)) in item_tables.iter().enumerate() {
+        let rows: Vec<_> = table.select(&row_selector).collect();
+        tracing::trace!(
+            section,
+            table = table_idx + 1,
+            rows = rows.len(),
+            "Parsing table"
+        );
+
+        for row in rows.iter().skip(1) {
+            let cells: Vec<_> = row.select(&cell_selector).collect();
+
+            if columns.fits(&cells) {
+                if let Some(name_cell) = columns.cell(&cells, NAME) {
+                    if let Some(name) = extract_name_from_cell(&name_cell) {
+                        let blueprint = columns
+                            .cell(&cells, BLUEPRINT)
+                            .and_then(|cell| extract_blueprint(&cell.text().collect::<String>()));
+                        let mod_id = blueprint.as_deref().map(|bp| ctx.mods.from_blueprint(bp));
+                        let row = RowFields {
+                            name: &name,
+                            blueprint: blueprint.as_deref(),
+                            mod_id: mod_id.as_deref(),
+                        };
+
+                        if let Some(rule) = ctx.rules.matching("items", &row) {
+                            section_sink.skip_by_rule(&name, rule, url);
+                        } else {
+                            let type_name = columns
+                                .cell(&cells, CATEGORY)
+                                .and_then(|cell| extract_type_from_cell(&cell))
+                                .unwrap_or_else(|| section.to_string());
+
+                            let class_name = columns
+                                .cell(&cells, CLASS_NAME)
+                                .and_then(|cell| extract_class_name_from_cell(&cell))
+                                .unwrap_or_else(|| "Unknown".to_string());
+
+                            match (blueprint, mod_id) {
+                                (Some(blueprint), Some(mod_id)) => {
+                                    let marker = columns
+                                        .cell(&cells, EDITION)
+                                        .map(|cell| cell.text().collect::<String>())
+                                        .unwrap_or_default();
+                                    let editions = ctx.mods.editions(&marker, &mod_id, ctx.edition);
+
+                                    tracing::trace!(
+                                        name = name.as_str(),
+                                        type_name = type_name.as_str(),
+                                        class_name = class_name.as_str(),
+                                        blueprint = blueprint.as_str(),
+                                        mod_id = mod_id.as_str(),
+                                        "Parsed item"
+                                    );
+
+                                    section_sink.insert(Item {
+                                        type_name,
+                                        name,
+                                        mod_id,
+                                        editions,
+                                        class_name,
+                                        blueprint,
+                                    });
+
+                                    result.items += 1;
+                                }
+                                _ => {
+                                    section_sink.skip(&name, "no blueprint found", url);
+                                    result.failed_items += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_direct_item_subpages_are_sections() {
+        assert_eq!(
+            section_name("Item IDs/Chibi Pets").as_deref(),
+            Some("Chibi Pets")
+        );
+        assert_eq!(
+            section_name("Item_IDs/Resources").as_deref(),
+            Some("Resources")
+        );
+        for title in [
+            "Item IDs/",
+            "Item IDs/de",
+            "Item IDs/zh-hans",
+            "Item IDs/Resources/fr",
+            "Creature IDs/Dodo",
+        ] {
+            assert_eq!(section_name(title), None, "{}", title);
+        }
     }
-    result
 }
@@ -0,0 +1,375 @@
+//! Key policy for every collection in `ArkData`.
+//!
+//! An entry's key is derived from what identifies it in the game rather than
+//! from its wiki display name, so renames on the wiki do not re-key it:
+//!
+//! - creatures, items and engrams: the blueprint's asset name, e.g.
+//!   `Dodo_Character_BP` (engrams without a known blueprint fall back to
+//!   their class name)
+//! - beacons: the class name without the trailing `_C`
+//! - colors: `Color_` followed by the color ID
+//! - icons: the asset name of the icon path
+//!
+//! Two different entries deriving the same key are kept apart as described
+//! on [`insert_keyed`].
+
+use super::registry::Category;
+use super::report::KeyCollision;
+use crate::types::blueprint::Blueprint;
+use crate::types::{ArkData, Beacon, Color, Creature, Engram, Icon, Item};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// An entry with a value that tells it apart from others sharing its name.
+pub trait Keyed {
+    fn identity(&self) -> String;
+
+    /// The key the entry is stored under; see the module docs.
+    fn key(&self) -> String {
+        let key = asset_name(&self.identity());
+        match key.strip_suffix("_C") {
+            Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+            _ => key,
+        }
+    }
+}
+
+impl Keyed for Creature {
+    fn identity(&self) -> String {
+        self.blueprint.clone()
+    }
+}
+
+impl Keyed for Item {
+    fn identity(&self) -> String {
+        self.blueprint.clone()
+    }
+}
+
+impl Keyed for Engram {
+    fn identity(&self) -> String {
+        if self.blueprint == "Unknown" {
+            self.class_name.clone()
+        } else {
+            self.blueprint.clone()
+        }
+    }
+}
+
+impl Keyed for Beacon {
+    fn identity(&self) -> String {
+        self.class_name.clone()
+    }
+}
+
+impl Keyed for Color {
+    fn identity(&self) -> String {
+        self.color_id.to_string()
+    }
+
+    fn key(&self) -> String {
+        format!("Color_{}", self.color_id)
+    }
+}
+
+impl Keyed for Icon {
+    fn identity(&self) -> String {
+        self.path.clone()
+    }
+}
+
+/// The key an entry of `category` given as JSON is stored under, for entries
+/// added by hand.
+pub fn entry_key(category: Category, data: Value) -> Result<String, String> {
+    fn key_of<T: Keyed + DeserializeOwned>(data: Value) -> Result<String, String> {
+        serde_json::from_value::<T>(data)
+            .map(|entry| entry.key())
+            .map_err(|e| format!("Invalid entry data: {}", e))
+    }
+
+    match category {
+        Category::Creatures => key_of::<Creature>(data),
+        Category::Items => key_of::<Item>(data),
+        Category::Engrams => key_of::<Engram>(data),
+        Category::Beacons => key_of::<Beacon>(data),
+        Category::Colors => key_of::<Color>(data),
+        Category::Icons => key_of::<Icon>(data),
+    }
+}
+
+/// Inserts `value` under `key`, keeping entries with different identities
+/// apart. Once two of them claim the same key, every entry for that key is
+/// stored under a suffixed key instead, so the result does not depend on the
+/// order rows were scraped in. Rows with the same identity replace each other.
+pub fn insert_keyed<T: Keyed>(
+    map: &mut HashMap<String, T>,
+    collisions: &mut Vec<KeyCollision>,
+    category: Category,
+    key: String,
+    value: T,
+) {
+    if let Some(collision) = find_collision(collisions, category, &key) {
+        let resolved = disambiguate(map, &key, &value);
+        map.insert(resolved.clone(), value);
+        if !collision.resolved.contains(&resolved) {
+            collision.resolved.push(resolved);
+        }
+        return;
+    }
+
+    match map.get(&key) {
+        Some(existing) if existing.identity() != value.identity() => {
+            let existing = map.remove(&key).unwrap();
+            let existing_key = disambiguate(map, &key, &existing);
+            map.insert(existing_key.clone(), existing);
+
+            let value_key = disambiguate(map, &key, &value);
+            map.insert(value_key.clone(), value);
+
+            collisions.push(KeyCollision {
+                category,
+                key,
+                resolved: vec![existing_key, value_key],
+            });
+        }
+        _ => {
+            map.insert(key, value);
+        }
+    }
+}
+
+/// Records `collision`, found while filling another sink, and moves any entry
+/// still stored under its plain key.
+pub fn merge_collision<T: Keyed>(
+    map: &mut HashMap<String, T>,
+    collisions: &mut Vec<KeyCollision>,
+    collision: KeyCollision,
+) {
+    match find_collision(collisions, collision.category, &collision.key) {
+        Some(known) => {
+            for key in collision.resolved {
+                if !known.resolved.contains(&key) {
+                    known.resolved.push(key);
+                }
+            }
+        }
+        None => {
+            let key = collision.key.clone();
+            let category = collision.category;
+            collisions.push(collision);
+            if let Some(existing) = map.remove(&key) {
+                insert_keyed(map, collisions, category, key, existing);
+            }
+        }
+    }
+}
+
+fn find_collision<'a>(
+    collisions: &'a mut [KeyCollision],
+    category: Category,
+    key: &str,
+) -> Option<&'a mut KeyCollision> {
+    collisions
+        .iter_mut()
+        .find(|c| c.category == category && c.key == key)
+}
+
+/// `key` with a suffix telling the entry apart: the folder its blueprint
+/// lives in, since colliding keys share an asset name, or else a hash of the
+/// identity.
+fn disambiguate<T: Keyed>(map: &HashMap<String, T>, key: &str, value: &T) -> String {
+    let identity = value.identity();
+    let hashed = || format!("{}_{:08x}", key, fnv1a(&identity) as u32);
+
+    let candidate = match blueprint_folder(&identity) {
+        Some(folder) => format!("{}_{}", key, folder),
+        None => return hashed(),
+    };
+
+    match map.get(&candidate) {
+        Some(existing) if existing.identity() != identity => hashed(),
+        _ => candidate,
+    }
+}
+
+/// The name of the folder holding a blueprint, e.g. `Foo` for
+/// `/Game/Mods/Foo/PrimalItemResource_Stone.PrimalItemResource_Stone`.
+fn blueprint_folder(identity: &str) -> Option<String> {
+    let blueprint = Blueprint::parse(identity).ok()?;
+    let folder: String = blueprint
+        .folder()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+
+    if folder.is_empty() {
+        None
+    } else {
+        Some(folder)
+    }
+}
+
+/// The asset name of a blueprint path, or the identity itself for class names
+/// and IDs, reduced to characters that are safe in a key.
+fn asset_name(identity: &str) -> String {
+    let asset = match Blueprint::parse(identity) {
+        Ok(blueprint) => blueprint.asset().to_string(),
+        // Class names, IDs and paths too malformed to parse
+        Err(_) => {
+            let asset = identity
+                .trim_matches(|c| c == '"' || c == '\'')
+                .rsplit('/')
+                .next()
+                .unwrap_or(identity);
+            asset.split('.').next().unwrap_or(asset).to_string()
+        }
+    };
+
+    asset
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// Stable across runs and platforms, unlike `DefaultHasher`.
+pub(super) fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The result of re-keying an existing database under the current policy.
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyMigration {
+    pub data: ArkData,
+    /// Old key to new key for every entry whose key changed, per collection.
+    pub key_map: BTreeMap<Category, BTreeMap<String, String>>,
+    pub collisions: Vec<KeyCollision>,
+}
+
+/// Re-keys every collection in `data`. Entries that share an identity under
+/// different old keys end up as one entry.
+pub fn migrate(mut data: ArkData) -> KeyMigration {
+    let mut key_map = BTreeMap::new();
+    let mut collisions = Vec::new();
+
+    data.creatures = rekey(
+        std::mem::take(&mut data.creatures),
+        Category::Creatures,
+        &mut key_map,
+        &mut collisions,
+    );
+    data.items = rekey(
+        std::mem::take(&mut data.items),
+        Category::Items,
+        &mut key_map,
+        &mut collisions,
+    );
+    data.engrams = rekey(
+        std::mem::take(&mut data.engrams),
+        Category::Engrams,
+        &mut key_map,
+        &mut collisions,
+    );
+    data.beacons = rekey(
+        std::mem::take(&mut data.beacons),
+        Category::Beacons,
+        &mut key_map,
+        &mut collisions,
+    );
+    data.colors = rekey(
+        std::mem::take(&mut data.colors),
+        Category::Colors,
+        &mut key_map,
+        &mut collisions,
+    );
+    data.icons = rekey(
+        std::mem::take(&mut data.icons),
+        Category::Icons,
+        &mut key_map,
+        &mut collisions,
+    );
+
+    KeyMigration {
+        data,
+        key_map,
+        collisions,
+    }
+}
+
+fn rekey<T: Keyed>(
+    entries: HashMap<String, T>,
+    category: Category,
+    key_map: &mut BTreeMap<Category, BTreeMap<String, String>>,
+    collisions: &mut Vec<KeyCollision>,
+) -> HashMap<String, T> {
+    // Sorted so duplicates resolve the same way on every run
+    let entries: BTreeMap<_, _> = entries.into_iter().collect();
+    let old_keys: Vec<(String, String)> = entries
+        .iter()
+        .map(|(key, value)| (key.clone(), value.identity()))
+        .collect();
+
+    let mut rekeyed = HashMap::new();
+    for (_, value) in entries {
+        let key = value.key();
+        insert_keyed(&mut rekeyed, collisions, category, key, value);
+    }
+
+    let new_keys: HashMap<String, &String> = rekeyed
+        .iter()
+        .map(|(key, value)| (value.identity(), key))
+        .collect();
+
+    let changed: BTreeMap<String, String> = old_keys
+        .into_iter()
+        .filter_map(|(old_key, identity)| {
+            let new_key = new_keys.get(&identity)?;
+            (old_key != **new_key).then(|| (old_key, new_key.to_string()))
+        })
+        .collect();
+
+    if !changed.is_empty() {
+        key_map.insert(category, changed);
+    }
+
+    rekeyed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn migration_maps_old_keys_to_policy_keys() {
+        let mut stone = test_support::item("Stone");
+        stone.name = "Raw Stone".to_string();
+        let mut data = ArkData::default();
+        data.items.insert("Raw_Stone".to_string(), stone);
+
+        let migration = migrate(data);
+
+        assert!(migration.data.items.contains_key("Stone"));
+        assert_eq!(migration.key_map[&Category::Items]["Raw_Stone"], "Stone");
+    }
+
+    #[test]
+    fn entries_added_by_hand_follow_the_key_policy() {
+        let stone = serde_json::to_value(test_support::item("Stone")).unwrap();
+        assert_eq!(entry_key(Category::Items, stone).unwrap(), "Stone");
+
+        let color = serde_json::json!({
+            "type_name": "color",
+            "name": "Red",
+            "color_id": 1,
+            "hex_code": "#FF0000",
+        });
+        assert_eq!(entry_key(Category::Colors, color).unwrap(), "Color_1");
+
+        let incomplete = serde_json::json!({ "name": "Stone" });
+        assert!(entry_key(Category::Items, incomplete).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Checks that a wiki page still has the shape the scrapers were written
+//! against. Required table headers are enforced by [`super::columns`]; this
+//! covers the number of tables on a page and how many entries a stage yields
+//! compared with the previous scrape.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Raised when a page or a stage's output no longer looks like it used to.
+#[derive(Debug)]
+pub struct LayoutDrift {
+    pub source: String,
+    pub problem: String,
+}
+
+impl fmt::Display for LayoutDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wiki layout changed at {}: {}",
+            self.source, self.problem
+        )
+    }
+}
+
+impl Error for LayoutDrift {}
+
+/// Fails unless `url` had at least `min` of `what` (e.g. tables).
+pub fn expect_at_least(url: &str, what: &str, found: usize, min: usize) -> Result<(), LayoutDrift> {
+    if found >= min {
+        return Ok(());
+    }
+
+    Err(LayoutDrift {
+        source: url.to_string(),
+        problem: format!("expected at least {} {}, found {}", min, what, found),
+    })
+}
+
+/// What the last successful scrape of each stage produced: how many entries,
+/// to catch a stage that suddenly returns far fewer rows, and which wiki
+/// sections it read, to report sections that appeared or vanished since.
+#[derive(Debug, Clone, Default)]
+pub struct Baseline {
+    entries: HashMap<String, usize>,
+    sections: HashMap<String, Vec<String>>,
+    min_ratio: f32,
+}
+
+impl Baseline {
+    pub fn new(min_ratio: f32) -> Self {
+        Self {
+            min_ratio,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a previous run of `stage`. Feed runs newest first; only the first
+    /// run recorded for a stage counts.
+    pub fn record(&mut self, stage: &str, entries: usize, sections: &[String]) {
+        self.entries.entry(stage.to_string()).or_insert(entries);
+        if !sections.is_empty() {
+            self.sections
+                .entry(stage.to_string())
+                .or_insert_with(|| sections.to_vec());
+        }
+    }
+
+    /// Stages the previous scrape did not cover always pass.
+    pub fn check(&self, stage: &str, entries: usize) -> Result<(), LayoutDrift> {
+        let previous = match self.entries.get(stage) {
+            Some(&previous) if previous > 0 => previous,
+            _ => return Ok(()),
+        };
+
+        let min = (previous as f32 * self.min_ratio).ceil() as usize;
+        if entries >= min {
+            return Ok(());
+        }
+
+        Err(LayoutDrift {
+            source: stage.to_string(),
+            problem: format!(
+                "collected {} entries, expected at least {} ({:.0}% of the {} from the previous scrape)",
+                entries,
+                min,
+                self.min_ratio * 100.0,
+                previous
+            ),
+        })
+    }
+
+    /// Sections `stage` read now but not last time, and the reverse. Both are
+    /// empty when there is no previous run to compare with.
+    pub fn section_changes(&self, stage: &str, sections: &[String]) -> (Vec<String>, Vec<String>) {
+        let previous = match self.sections.get(stage) {
+            Some(previous) => previous,
+            None => return (Vec::new(), Vec::new()),
+        };
+
+        let added = sections
+            .iter()
+            .filter(|section| !previous.contains(section))
+            .cloned()
+            .collect();
+        let vanished = previous
+            .iter()
+            .filter(|section| !sections.contains(section))
+            .cloned()
+            .collect();
+
+        (added, vanished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_far_below_the_previous_scrape_are_rejected() {
+        let mut baseline = Baseline::new(0.5);
+        baseline.record("creatures", 400, &[]);
+
+        assert!(baseline.check("creatures", 200).is_ok());
+        assert!(baseline.check("creatures", 12).is_err());
+        // No previous run to compare against
+        assert!(baseline.check("beacons", 0).is_ok());
+    }
+
+    #[test]
+    fn section_changes_are_reported_against_the_previous_scrape() {
+        let sections = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let mut baseline = Baseline::new(0.5);
+        baseline.record("items", 300, &sections(&["Resources", "Trophies"]));
+
+        let (added, vanished) =
+            baseline.section_changes("items", &sections(&["Chibi Pets", "Resources"]));
+        assert_eq!(added, sections(&["Chibi Pets"]));
+        assert_eq!(vanished, sections(&["Trophies"]));
+        assert_eq!(
+            baseline.section_changes("engrams", &sections(&["Engrams"])),
+            (Vec::new(), Vec::new())
+        );
+    }
+}
@@ -1,72 +1,307 @@
 // src-tauri/src/scrapers/mod.rs
 pub mod beacons;
+pub mod checkpoint;
 pub mod colors;
+pub mod columns;
 pub mod common;
+pub mod config;
 pub mod creatures;
 pub mod engrams;
-pub mod icons; // Add this line
+pub mod fixtures;
+pub mod icons;
 pub mod items;
+pub mod keys;
+pub mod layout;
+pub mod mods;
 pub mod progress;
+pub mod registry;
+pub mod report;
+pub mod revisions;
+pub mod rules;
 
-use crate::types::ArkData;
-use std::collections::HashMap;
-use tauri::Window;
+#[cfg(test)]
+mod tests;
 
+use crate::types::{ArkData, Edition};
+use checkpoint::Checkpoint;
+use common::ScraperClient;
+use futures::future;
+use layout::Baseline;
+use mods::ModMap;
+use progress::{ProgressSink, ProgressTracker, ScrapingProgress};
+use registry::{ScrapeContext, ScrapeSink, ScraperRegistry, UnitsFailed};
+use report::{ScrapeReport, StageReport};
+use revisions::RevisionCache;
+use rules::SkipRules;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Scraped data together with the report describing how it was collected.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScrapeOutcome {
+    pub data: ArkData,
+    pub report: ScrapeReport,
+}
+
+impl ScrapeOutcome {
+    /// Adds what a run retrying this outcome's failed units collected, and
+    /// updates the affected stage reports, and the run's error, to match.
+    pub fn merge_retry(&mut self, retry: ScrapeOutcome) {
+        self.data.merge_missing(retry.data);
+        self.data.last_updated = retry.report.finished_at;
+
+        for stage in retry.report.stages {
+            if stage.error.is_some() {
+                continue;
+            }
+            if let Some(report) = self
+                .report
+                .stages
+                .iter_mut()
+                .find(|s| s.stage == stage.stage)
+            {
+                report.merge_retry(stage);
+                if let Some(entries) = collection_len(&self.data, &report.stage) {
+                    report.entries = entries;
+                }
+            }
+        }
+        self.report.error = ScrapeReport::first_error(&self.report.stages);
+        self.report.finished_at = retry.report.finished_at;
+        self.report.duration_ms += retry.report.duration_ms;
+    }
+}
+
+/// How many entries `data` holds for `stage`. Stages are named after the
+/// collection they fill.
+fn collection_len(data: &ArkData, stage: &str) -> Option<usize> {
+    match stage {
+        "creatures" => Some(data.creatures.len()),
+        "items" => Some(data.items.len()),
+        "engrams" => Some(data.engrams.len()),
+        "beacons" => Some(data.beacons.len()),
+        "colors" => Some(data.colors.len()),
+        "icons" => Some(data.icons.len()),
+        _ => None,
+    }
+}
+
+/// What a run needs besides the client: the user's rules and what earlier
+/// runs left behind.
+pub struct ScrapeInputs {
+    pub checkpoint: Checkpoint,
+    pub rules: SkipRules,
+    pub mods: ModMap,
+    pub baseline: Baseline,
+    /// The `icons.txt` list; the icons stage fails without it.
+    pub icons_file: Option<PathBuf>,
+    pub revisions: RevisionCache,
+    /// Edition of entries nothing else assigns one to.
+    pub edition: Option<Edition>,
+    /// Failed units per stage from an earlier run. When set, only those
+    /// units are scraped, and stages without any are left out.
+    pub retry: Option<BTreeMap<String, Vec<String>>>,
+}
+
+/// Runs every registered scraper. Finished stages and units are recorded in
+/// the checkpoint, and stages it already holds are restored instead of
+/// scraped. The checkpoint is cleared once every stage has succeeded.
+///
+/// A stage that yields far fewer entries than the baseline recorded for it
+/// fails, as does one whose pages no longer have the expected shape.
+///
+/// The outcome is returned even when a stage fails; `report.error` then holds
+/// the first failure and `data` only what the successful stages collected.
+#[tracing::instrument(skip_all)]
 pub async fn scrape_all(
-    window: Window,
-) -> Result<ArkData, Box<dyn std::error::Error + Send + Sync>> {
+    progress: impl ProgressSink + 'static,
+    client: ScraperClient,
+    registry: &ScraperRegistry,
+    inputs: ScrapeInputs,
+) -> ScrapeOutcome {
+    let ScrapeInputs {
+        checkpoint,
+        rules,
+        mods,
+        baseline,
+        icons_file,
+        revisions,
+        edition,
+        retry,
+    } = inputs;
+    let started_at = chrono::Utc::now();
+    let run_timer = Instant::now();
+
     let mut ark_data = ArkData {
         creatures: HashMap::new(),
         items: HashMap::new(),
         engrams: HashMap::new(),
         beacons: HashMap::new(),
         colors: HashMap::new(),
-        icons: HashMap::new(), // Add this line
+        icons: HashMap::new(),
+        mods: HashMap::new(),
         version: "1.0.0".to_string(),
-        last_updated: chrono::Utc::now().timestamp(),
+        last_updated: started_at.timestamp(),
+    };
+
+    let scrapers: Vec<_> = registry
+        .scrapers()
+        .iter()
+        .filter(|s| match &retry {
+            Some(retry) => retry.contains_key(s.name()),
+            None => true,
+        })
+        .collect();
+
+    let ctx = ScrapeContext {
+        progress: ProgressTracker::new(progress, scrapers.iter().map(|s| (s.name(), s.weight()))),
+        icons_file,
+        client,
+        checkpoint,
+        rules,
+        mods,
+        revisions,
+        edition,
     };
 
-    // Track progress stages
-    let stages = [
-        ("creatures", "Starting creature scraping..."),
-        ("items", "Starting item scraping..."),
-        ("engrams", "Starting engram scraping..."),
-        ("beacons", "Starting beacon scraping..."),
-        ("colors", "Starting color scraping..."),
-        ("icons", "Starting icon scraping..."), // Add this line
-    ];
-
-    let total_stages = stages.len() as f32;
-
-    for (index, (stage, message)) in stages.iter().enumerate() {
-        let current_progress = (index as f32 / total_stages) * 100.0;
-
-        // Emit start progress
-        progress::emit_progress(&window, stage, current_progress, message);
-
-        // Perform scraping based on stage
-        match *stage {
-            "creatures" => creatures::scrape_creatures(&window, &mut ark_data.creatures).await?,
-            "items" => items::scrape_items(&window, &mut ark_data.items).await?,
-            "engrams" => engrams::scrape_engrams(&window, &mut ark_data.engrams).await?,
-            "beacons" => beacons::scrape_beacons(&window, &mut ark_data.beacons).await?,
-            "colors" => colors::scrape_colors(&window, &mut ark_data.colors).await?,
-            "icons" => icons::scrape_icons(&window, &mut ark_data.icons).await?, // Add this line
-            _ => {}
+    // Stages share no state, so run them side by side. The shared client
+    // keeps the combined request rate within the configured limit.
+    let stages = scrapers.iter().map(|scraper| {
+        let ctx = &ctx;
+        let baseline = &baseline;
+        let stage = scraper.name();
+        let units = retry.as_ref().and_then(|retry| retry.get(stage));
+        async move {
+            let stage_timer = Instant::now();
+
+            if let Some(sink) = ctx.checkpoint.completed_stage(stage) {
+                tracing::info!(entries = sink.len(), "Restored stage from checkpoint");
+                ScrapingProgress::new(stage, 100.0, &format!("{} restored from checkpoint", stage))
+                    .emit(&ctx.progress);
+                return (
+                    stage_report(stage, &sink, stage_timer, true, None),
+                    Some(sink),
+                );
+            }
+
+            ScrapingProgress::new(stage, 0.0, &format!("Starting {} scraping...", stage))
+                .emit(&ctx.progress);
+
+            let mut sink = ScrapeSink::new();
+            let result = match units {
+                // A retry only covers part of the stage, so the baseline doesn't apply
+                Some(units) => scraper.retry_units(ctx, units, &mut sink).await,
+                None => scraper
+                    .scrape(ctx, &mut sink)
+                    .await
+                    .and_then(|()| baseline.check(stage, sink.len()).map_err(Into::into)),
+            };
+
+            match result {
+                Ok(()) => {
+                    tracing::info!(
+                        entries = sink.len(),
+                        skipped = sink.skipped().len(),
+                        failed_fetches = sink.failed_fetches().len(),
+                        "Stage complete"
+                    );
+                    ctx.checkpoint.complete_stage(stage, &sink);
+                    ctx.revisions.commit(stage);
+                    ScrapingProgress::new(stage, 100.0, &format!("{} complete", stage))
+                        .emit(&ctx.progress);
+                    (
+                        stage_report(stage, &sink, stage_timer, false, None),
+                        Some(sink),
+                    )
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Stage failed");
+                    let mut report =
+                        stage_report(stage, &sink, stage_timer, false, Some(e.to_string()));
+                    report.failed_by_units = e.is::<UnitsFailed>();
+                    (report, None)
+                }
+            }
+        }
+        .instrument(tracing::info_span!("stage", stage))
+    });
+
+    // Let every stage finish, even after one fails, so its work is checkpointed
+    let mut stage_reports = Vec::new();
+    for (mut report, sink) in future::join_all(stages).await {
+        if let Some(sink) = sink {
+            if retry.is_some() {
+                sink.drain_into(&mut ark_data);
+                stage_reports.push(report);
+                continue;
+            }
+
+            let (new_sections, vanished_sections) =
+                baseline.section_changes(&report.stage, &report.sections);
+            if !new_sections.is_empty() || !vanished_sections.is_empty() {
+                tracing::warn!(
+                    stage = report.stage.as_str(),
+                    new = ?new_sections,
+                    vanished = ?vanished_sections,
+                    "Wiki sections changed since the previous scrape"
+                );
+            }
+            report.new_sections = new_sections;
+            report.vanished_sections = vanished_sections;
+
+            sink.drain_into(&mut ark_data);
         }
+        stage_reports.push(report);
+    }
+
+    mods::register_referenced(&mut ark_data, &ctx.mods);
+
+    let error = ScrapeReport::first_error(&stage_reports);
 
-        // Emit completion for this stage
-        let stage_complete_progress = ((index + 1) as f32 / total_stages) * 100.0;
-        progress::emit_progress(
-            &window,
-            stage,
-            stage_complete_progress,
-            &format!("{} complete", stage),
-        );
+    if error.is_some() {
+        ctx.checkpoint.save();
+    } else {
+        ctx.checkpoint.clear();
+
+        // Final completion message
+        ctx.progress.finish("complete", "Data collection complete");
     }
 
-    // Final completion message
-    progress::emit_progress(&window, "complete", 100.0, "Data collection complete");
+    ScrapeOutcome {
+        data: ark_data,
+        report: ScrapeReport {
+            started_at: started_at.timestamp(),
+            finished_at: chrono::Utc::now().timestamp(),
+            duration_ms: run_timer.elapsed().as_millis() as u64,
+            stages: stage_reports,
+            error,
+        },
+    }
+}
 
-    Ok(ark_data)
+fn stage_report(
+    stage: &str,
+    sink: &ScrapeSink,
+    started: Instant,
+    restored: bool,
+    error: Option<String>,
+) -> StageReport {
+    StageReport {
+        stage: stage.to_string(),
+        entries: sink.len(),
+        skipped: sink.skipped().to_vec(),
+        failed_fetches: sink.failed_fetches().to_vec(),
+        failed_units: sink.failed_units().to_vec(),
+        collisions: sink.collisions().to_vec(),
+        sections: sink.sections().to_vec(),
+        new_sections: Vec::new(),
+        vanished_sections: Vec::new(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        restored,
+        error,
+        failed_by_units: false,
+    }
 }
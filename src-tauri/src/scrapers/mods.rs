@@ -0,0 +1,464 @@
+//! Which mod or DLC an entry comes from, looked up in an editable table of
+//! blueprint path fragments and wiki section headers, and the registry of
+//! mods that entries reference by ID.
+
+use super::registry::Category;
+use crate::types::{ArkData, Edition, Mod};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// `mod_id` of entries no mapping matched.
+pub const UNKNOWN_MOD: &str = "unknown";
+
+/// One mod or DLC and how to recognise its entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModMapping {
+    /// The registry entry; its `id` is stored as the entries' `mod_id`.
+    #[serde(flatten)]
+    pub info: Mod,
+    /// Blueprint path fragments such as `/ScorchedEarth/`. They are matched
+    /// anywhere in the path, so both `/Game/X/` and `/Game/Mods/X/` match.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Text of the wiki section headers that group this mod's entries,
+    /// matched case-insensitively anywhere in the header.
+    #[serde(default)]
+    pub headers: Vec<String>,
+}
+
+impl ModMapping {
+    fn official(
+        id: &str,
+        name: &str,
+        blueprint_prefix: Option<&str>,
+        paths: &[&str],
+        headers: &[&str],
+    ) -> Self {
+        Self {
+            info: Mod {
+                id: id.to_string(),
+                name: name.to_string(),
+                workshop_id: None,
+                blueprint_prefix: blueprint_prefix.map(str::to_string),
+                official: true,
+                editions: Vec::new(),
+            },
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    /// Marks content that was only released for `edition`.
+    fn only_in(mut self, edition: Edition) -> Self {
+        self.info.editions = vec![edition];
+        self
+    }
+}
+
+/// The user-editable mod table. Mappings are tried in order, so a more
+/// specific one (`Genesis Part 2`) has to come before a broader one
+/// (`Genesis`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModMap {
+    pub mods: Vec<ModMapping>,
+}
+
+impl Default for ModMap {
+    fn default() -> Self {
+        let m = ModMapping::official;
+        let mods = vec![
+            m(
+                "ark",
+                "Ark",
+                Some("/Game/PrimalEarth/"),
+                &["/PrimalEarth/"],
+                &["The Island", "Base Game", "Vanilla"],
+            ),
+            m(
+                "scorched-earth",
+                "Scorched Earth",
+                Some("/Game/ScorchedEarth/"),
+                &["/ScorchedEarth/"],
+                &["Scorched Earth"],
+            ),
+            m(
+                "aberration",
+                "Aberration",
+                Some("/Game/Aberration/"),
+                &["/Aberration/"],
+                &["Aberration"],
+            ),
+            m(
+                "extinction",
+                "Extinction",
+                Some("/Game/Extinction/"),
+                &["/Extinction/"],
+                &["Extinction"],
+            ),
+            m(
+                "genesis-2",
+                "Genesis 2",
+                Some("/Game/Genesis2/"),
+                &["/Gen2/", "/Genesis2/"],
+                &["Genesis: Part 2", "Genesis Part 2", "Genesis 2"],
+            ),
+            m(
+                "genesis",
+                "Genesis",
+                Some("/Game/Genesis/"),
+                &["/Genesis/"],
+                &["Genesis"],
+            ),
+            m(
+                "the-center",
+                "The Center",
+                Some("/Game/Mods/TheCenter/"),
+                &["/TheCenter/"],
+                &["The Center"],
+            ),
+            m(
+                "ragnarok",
+                "Ragnarok",
+                Some("/Game/Mods/Ragnarok/"),
+                &["/Ragnarok/"],
+                &["Ragnarok"],
+            ),
+            m(
+                "valguero",
+                "Valguero",
+                Some("/Game/Mods/Valguero/"),
+                &["/Valguero/"],
+                &["Valguero"],
+            ),
+            m(
+                "crystal-isles",
+                "Crystal Isles",
+                Some("/Game/Mods/CrystalIsles/"),
+                &["/CrystalIsles/"],
+                &["Crystal Isles"],
+            ),
+            m(
+                "lost-island",
+                "Lost Island",
+                None,
+                &["/LostIsland/"],
+                &["Lost Island"],
+            ),
+            m(
+                "fjordur",
+                "Fjordur",
+                None,
+                &["/Fjordur/"],
+                &["Fjordur", "Fjördur"],
+            ),
+            m("club-ark", "Club ARK", None, &["/ClubArk/"], &["Club ARK"]).only_in(Edition::Asa),
+            m("astraeos", "Astraeos", None, &["/Astraeos/"], &["Astraeos"]).only_in(Edition::Asa),
+            m(
+                "lost-colony",
+                "Lost Colony",
+                None,
+                &["/LostColony/"],
+                &["Lost Colony"],
+            )
+            .only_in(Edition::Asa),
+        ];
+
+        Self { mods }
+    }
+}
+
+impl ModMap {
+    /// Rejects mappings without an ID, name or anything to match on, and
+    /// IDs used twice.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = BTreeSet::new();
+        for mapping in &self.mods {
+            let info = &mapping.info;
+            if info.id.trim().is_empty() || info.name.trim().is_empty() {
+                return Err("Mod mapping has no ID or name".to_string());
+            }
+            if info.id == UNKNOWN_MOD || !ids.insert(info.id.as_str()) {
+                return Err(format!("Mod ID '{}' is already taken", info.id));
+            }
+            let patterns = mapping.paths.iter().chain(&mapping.headers);
+            if patterns.clone().any(|p| p.trim().is_empty()) {
+                return Err(format!("Mod mapping '{}' has an empty pattern", info.id));
+            }
+            if patterns.count() == 0 {
+                return Err(format!("Mod mapping '{}' has no patterns", info.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// ID of the mod whose path fragments `blueprint` contains, or
+    /// [`UNKNOWN_MOD`].
+    pub fn from_blueprint(&self, blueprint: &str) -> String {
+        self.mods
+            .iter()
+            .find(|m| m.paths.iter().any(|path| blueprint.contains(path.as_str())))
+            .map_or(UNKNOWN_MOD, |m| m.info.id.as_str())
+            .to_string()
+    }
+
+    /// ID of the mod a wiki section `header` names, or [`UNKNOWN_MOD`].
+    pub fn from_header(&self, header: &str) -> String {
+        self.find_by_header(header)
+            .map_or(UNKNOWN_MOD, |m| m.info.id.as_str())
+            .to_string()
+    }
+
+    fn find_by_header(&self, header: &str) -> Option<&ModMapping> {
+        let header = header.to_lowercase();
+        self.mods.iter().find(|m| {
+            m.headers
+                .iter()
+                .any(|pattern| header.contains(&pattern.to_lowercase()))
+        })
+    }
+
+    /// The registry entry for `id`. Unmapped IDs, such as [`UNKNOWN_MOD`],
+    /// get a placeholder named after the ID.
+    pub fn registry_entry(&self, id: &str) -> Mod {
+        match self.mods.iter().find(|m| m.info.id == id) {
+            Some(mapping) => mapping.info.clone(),
+            None if id == UNKNOWN_MOD => unofficial(id, "Unknown"),
+            None => unofficial(id, id),
+        }
+    }
+
+    /// Editions of an entry of `mod_id`: those `marker` (an edition column
+    /// or section header) names, else the ones the mod was released for,
+    /// else `fallback`, the edition the scraped wiki covers.
+    pub fn editions(&self, marker: &str, mod_id: &str, fallback: Option<Edition>) -> Vec<Edition> {
+        let named = Edition::mentioned_in(marker);
+        if !named.is_empty() {
+            return named;
+        }
+
+        match self.mods.iter().find(|m| m.info.id == mod_id) {
+            Some(mapping) if !mapping.info.editions.is_empty() => mapping.info.editions.clone(),
+            _ => fallback.into_iter().collect(),
+        }
+    }
+
+    /// Turns a free-text mod name from before the registry into an ID,
+    /// recognising mapped IDs, names and header spellings such as "Vanilla",
+    /// or [`UNKNOWN_MOD`]. Unlike headers on the wiki, the whole name has to
+    /// match, so "Ark Additions" is not taken for "ark".
+    fn resolve_name(&self, name: &str) -> String {
+        self.mods
+            .iter()
+            .find(|m| {
+                m.info.id == name
+                    || m.info.name.eq_ignore_ascii_case(name)
+                    || m.headers.iter().any(|h| h.eq_ignore_ascii_case(name))
+            })
+            .map_or(UNKNOWN_MOD, |m| m.info.id.as_str())
+            .to_string()
+    }
+}
+
+fn unofficial(id: &str, name: &str) -> Mod {
+    Mod {
+        id: id.to_string(),
+        name: name.to_string(),
+        workshop_id: None,
+        blueprint_prefix: None,
+        official: false,
+        editions: Vec::new(),
+    }
+}
+
+fn mod_ids_mut(data: &mut ArkData) -> Vec<&mut String> {
+    let creatures = data.creatures.values_mut().map(|c| &mut c.mod_id);
+    let items = data.items.values_mut().map(|i| &mut i.mod_id);
+    let engrams = data.engrams.values_mut().map(|e| &mut e.mod_id);
+    let beacons = data.beacons.values_mut().map(|b| &mut b.mod_id);
+    creatures
+        .chain(items)
+        .chain(engrams)
+        .chain(beacons)
+        .collect()
+}
+
+/// Adds a registry entry for every mod an entry references that `data`
+/// does not list yet. Returns the IDs added.
+pub fn register_referenced(data: &mut ArkData, map: &ModMap) -> Vec<String> {
+    let referenced: BTreeSet<String> = mod_ids_mut(data).into_iter().map(|id| id.clone()).collect();
+
+    let mut added = Vec::new();
+    for id in referenced {
+        if !data.mods.contains_key(&id) {
+            data.mods.insert(id.clone(), map.registry_entry(&id));
+            added.push(id);
+        }
+    }
+    added
+}
+
+/// What [`migrate`] changed.
+#[derive(Debug, Serialize, Clone)]
+pub struct ModMigration {
+    pub data: ArkData,
+    /// Old free-text mod name to the ID that replaced it.
+    pub renamed: BTreeMap<String, String>,
+    /// Mods added to the registry.
+    pub registered: Vec<String>,
+}
+
+/// Replaces free-text mod names with registry IDs, so "Ark" and "Vanilla"
+/// become the same mod, and registers every mod referenced. Names no
+/// mapping matches exactly become [`UNKNOWN_MOD`], so they show up in
+/// [`unknown_entries`] to be sorted out by hand.
+pub fn migrate(mut data: ArkData, map: &ModMap) -> ModMigration {
+    let known: BTreeSet<String> = data.mods.keys().cloned().collect();
+    let mut renamed = BTreeMap::new();
+
+    for mod_id in mod_ids_mut(&mut data) {
+        if known.contains(mod_id.as_str()) {
+            continue;
+        }
+
+        let id = map.resolve_name(mod_id);
+        if id != *mod_id {
+            renamed.insert(mod_id.clone(), id.clone());
+            *mod_id = id;
+        }
+    }
+
+    let registered = register_referenced(&mut data, map);
+
+    ModMigration {
+        data,
+        renamed,
+        registered,
+    }
+}
+
+/// An entry whose mod could not be determined.
+#[derive(Debug, Serialize, Clone)]
+pub struct UnknownModEntry {
+    pub category: Category,
+    pub key: String,
+    pub name: String,
+    /// Beacons have no blueprint to go by.
+    pub blueprint: Option<String>,
+}
+
+/// Entries classified as [`UNKNOWN_MOD`], sorted by category and key, so the
+/// table can be extended to cover them.
+pub fn unknown_entries(data: &ArkData) -> Vec<UnknownModEntry> {
+    let mut entries = Vec::new();
+    let mut add = |category, key: &String, name: &str, mod_id: &str, blueprint: Option<&str>| {
+        if mod_id == UNKNOWN_MOD {
+            entries.push(UnknownModEntry {
+                category,
+                key: key.clone(),
+                name: name.to_string(),
+                blueprint: blueprint.map(str::to_string),
+            });
+        }
+    };
+
+    for (key, c) in &data.creatures {
+        add(
+            Category::Creatures,
+            key,
+            &c.name,
+            &c.mod_id,
+            Some(&c.blueprint),
+        );
+    }
+    for (key, i) in &data.items {
+        add(Category::Items, key, &i.name, &i.mod_id, Some(&i.blueprint));
+    }
+    for (key, e) in &data.engrams {
+        add(
+            Category::Engrams,
+            key,
+            &e.name,
+            &e.mod_id,
+            Some(&e.blueprint),
+        );
+    }
+    for (key, b) in &data.beacons {
+        add(Category::Beacons, key, &b.name, &b.mod_id, None);
+    }
+
+    entries.sort_by(|a, b| (a.category, &a.key).cmp(&(b.category, &b.key)));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::item;
+    use crate::types::Item;
+    use crate::validation;
+
+    #[test]
+    fn free_text_mod_names_migrate_to_registered_ids() {
+        let mut data = ArkData::default();
+        for (name, mod_name) in [
+            ("Stone", "Ark"),
+            ("Wood", "Vanilla"),
+            ("Gem", "My Mod"),
+            ("Flint", "Ark Additions"),
+        ] {
+            // Saved before the registry, under the old field name
+            let mut entry = serde_json::to_value(item(name)).unwrap();
+            let fields = entry.as_object_mut().unwrap();
+            fields.remove("mod_id");
+            fields.insert("mod_name".to_string(), mod_name.into());
+            data.items
+                .insert(name.to_string(), serde_json::from_value(entry).unwrap());
+        }
+        assert_eq!(validation::validate_data(&data).len(), 4);
+
+        let migration = migrate(data, &ModMap::default());
+        let data = &migration.data;
+        assert_eq!(data.items["Stone"].mod_id, "ark");
+        assert_eq!(data.items["Wood"].mod_id, "ark");
+        // Only whole names match; the rest is left for the user to sort out
+        assert_eq!(data.items["Gem"].mod_id, UNKNOWN_MOD);
+        assert_eq!(data.items["Flint"].mod_id, UNKNOWN_MOD);
+        assert_eq!(migration.registered, ["ark", UNKNOWN_MOD]);
+        assert_eq!(unknown_entries(data).len(), 2);
+        assert!(validation::validate_data(data).is_empty());
+    }
+
+    #[test]
+    fn mod_map_classifies_paths_and_headers_in_order() {
+        let mods = ModMap::default();
+        assert_eq!(
+            mods.from_blueprint("Blueprint'/Game/Mods/Ragnarok/Dinos/Wyvern.Wyvern'"),
+            "ragnarok"
+        );
+        assert_eq!(
+            mods.from_blueprint("Blueprint'/Game/Genesis2/Dinos/Tek.Tek'"),
+            "genesis-2"
+        );
+        assert_eq!(mods.from_blueprint("Blueprint'/Game/Other/X.X'"), "unknown");
+        assert_eq!(mods.from_header("Genesis: Part 2"), "genesis-2");
+        assert_eq!(mods.from_header("lost island"), "lost-island");
+        assert_eq!(mods.from_header("Event creatures"), "unknown");
+
+        let mut data = ArkData::default();
+        data.items.insert(
+            "Mystery".to_string(),
+            Item {
+                type_name: "Misc".to_string(),
+                name: "Mystery".to_string(),
+                mod_id: "unknown".to_string(),
+                editions: Vec::new(),
+                class_name: "Unknown".to_string(),
+                blueprint: "Blueprint'/Game/Other/Mystery.Mystery'".to_string(),
+            },
+        );
+        let unknown = unknown_entries(&data);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].category, Category::Items);
+        assert_eq!(unknown[0].key, "Mystery");
+    }
+}
@@ -1,11 +1,24 @@
 use serde::Serialize;
-use tauri::Window;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
 
+/// Updates reaching the sink are at least this far apart, except for a
+/// stage starting or finishing and the final update of a run.
+const MIN_SEND_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A progress update from one stage, with the stage's own 0-100 percentage.
 #[derive(Serialize, Clone)]
 pub struct ScrapingProgress {
     pub stage: String,
     pub progress: f32,
     pub message: String,
+    /// Rows or pages the stage has handled so far.
+    pub items: usize,
+    /// Fetches or rows that failed so far.
+    pub errors: usize,
+    /// The page the stage is working on.
+    pub current_url: Option<String>,
 }
 
 impl ScrapingProgress {
@@ -14,17 +27,296 @@ impl ScrapingProgress {
             stage: stage.to_string(),
             progress,
             message: message.to_string(),
+            items: 0,
+            errors: 0,
+            current_url: None,
+        }
+    }
+
+    pub fn items(mut self, items: usize) -> Self {
+        self.items = items;
+        self
+    }
+
+    pub fn errors(mut self, errors: usize) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    pub fn url(mut self, url: &str) -> Self {
+        self.current_url = Some(url.to_string());
+        self
+    }
+
+    pub fn emit(&self, tracker: &ProgressTracker) {
+        tracker.report(self);
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct StageProgress {
+    pub stage: String,
+    pub progress: f32,
+    pub message: String,
+    pub weight: f32,
+    pub items: usize,
+    pub errors: usize,
+    pub items_per_second: f32,
+    /// Estimated seconds until the stage finishes, once it has made progress.
+    pub eta_secs: Option<u64>,
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl StageProgress {
+    fn apply(&mut self, update: &ScrapingProgress) {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let elapsed = started.elapsed().as_secs_f32();
+
+        self.progress = update.progress.clamp(0.0, 100.0);
+        self.message = update.message.clone();
+        self.items = update.items;
+        self.errors = update.errors;
+        self.items_per_second = if elapsed > 0.0 {
+            update.items as f32 / elapsed
+        } else {
+            0.0
+        };
+        self.eta_secs = eta(elapsed, self.progress);
+    }
+}
+
+/// Payload of the `scraping-progress` event. `stage`, `progress` and
+/// `message` describe the latest update against the overall run so a single
+/// progress bar stays meaningful while stages run side by side.
+#[derive(Serialize, Clone)]
+pub struct CombinedProgress {
+    pub stage: String,
+    pub progress: f32,
+    pub message: String,
+    pub current_url: Option<String>,
+    /// Failures across all stages so far.
+    pub errors: usize,
+    /// Combined rate of the stages still running.
+    pub items_per_second: f32,
+    /// Estimated seconds until the whole run finishes.
+    pub eta_secs: Option<u64>,
+    pub stages: Vec<StageProgress>,
+}
+
+/// Where a run's combined progress goes. Scrapers only ever see the
+/// [`ProgressTracker`], so they run the same under any front end.
+pub trait ProgressSink: Send + Sync {
+    fn send(&self, progress: &CombinedProgress);
+}
+
+/// Forwards every update to a channel, for background jobs and tests.
+pub struct ChannelSink(pub UnboundedSender<CombinedProgress>);
+
+impl ProgressSink for ChannelSink {
+    fn send(&self, progress: &CombinedProgress) {
+        // The receiver going away just means nobody is watching anymore
+        let _ = self.0.send(progress.clone());
+    }
+}
+
+/// Prints one line per update, for the command line.
+pub struct StdoutSink;
+
+impl ProgressSink for StdoutSink {
+    fn send(&self, progress: &CombinedProgress) {
+        println!(
+            "[{:>5.1}%] {}: {}",
+            progress.progress, progress.stage, progress.message
+        );
+    }
+}
+
+/// Drops every update.
+pub struct NoopSink;
+
+impl ProgressSink for NoopSink {
+    fn send(&self, _progress: &CombinedProgress) {}
+}
+
+struct TrackerState {
+    stages: Vec<StageProgress>,
+    last_sent: Option<Instant>,
+}
+
+/// Folds per-stage updates from concurrently running scrapers into one
+/// weighted overall percentage, and passes them on to the sink at a bounded
+/// rate so tight loops don't flood it.
+pub struct ProgressTracker {
+    sink: Box<dyn ProgressSink>,
+    started: Instant,
+    state: Mutex<TrackerState>,
+}
+
+impl ProgressTracker {
+    /// Stages are given as `(name, weight)` in display order.
+    pub fn new<'a>(
+        sink: impl ProgressSink + 'static,
+        stages: impl IntoIterator<Item = (&'a str, f32)>,
+    ) -> Self {
+        let stages = stages
+            .into_iter()
+            .map(|(stage, weight)| StageProgress {
+                stage: stage.to_string(),
+                progress: 0.0,
+                message: "Waiting...".to_string(),
+                weight,
+                items: 0,
+                errors: 0,
+                items_per_second: 0.0,
+                eta_secs: None,
+                started: None,
+            })
+            .collect();
+
+        Self {
+            sink: Box::new(sink),
+            started: Instant::now(),
+            state: Mutex::new(TrackerState {
+                stages,
+                last_sent: None,
+            }),
+        }
+    }
+
+    pub fn report(&self, update: &ScrapingProgress) {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            let boundary = update.progress <= 0.0 || update.progress >= 100.0;
+            if let Some(stage) = state.stages.iter_mut().find(|s| s.stage == update.stage) {
+                stage.apply(update);
+            }
+
+            let due = match state.last_sent {
+                Some(last_sent) => last_sent.elapsed() >= MIN_SEND_INTERVAL,
+                None => true,
+            };
+            if !boundary && !due {
+                return;
+            }
+            state.last_sent = Some(Instant::now());
+
+            self.snapshot(
+                &state.stages,
+                &update.stage,
+                &update.message,
+                update.current_url.clone(),
+            )
+        };
+
+        self.sink.send(&snapshot);
+    }
+
+    /// Marks every stage done and sends a final update under `stage`.
+    pub fn finish(&self, stage: &str, message: &str) {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            for stage in state.stages.iter_mut() {
+                stage.progress = 100.0;
+                stage.eta_secs = Some(0);
+            }
+            state.last_sent = Some(Instant::now());
+
+            self.snapshot(&state.stages, stage, message, None)
+        };
+
+        self.sink.send(&snapshot);
+    }
+
+    fn snapshot(
+        &self,
+        stages: &[StageProgress],
+        stage: &str,
+        message: &str,
+        current_url: Option<String>,
+    ) -> CombinedProgress {
+        let progress = overall_progress(stages);
+
+        CombinedProgress {
+            stage: stage.to_string(),
+            progress,
+            message: message.to_string(),
+            current_url,
+            errors: stages.iter().map(|s| s.errors).sum(),
+            items_per_second: stages
+                .iter()
+                .filter(|s| s.progress < 100.0)
+                .map(|s| s.items_per_second)
+                .sum(),
+            eta_secs: eta(self.started.elapsed().as_secs_f32(), progress),
+            stages: stages.to_vec(),
         }
     }
+}
+
+/// Extrapolates the time left from the time taken so far, assuming the
+/// remaining percentage goes at the same pace.
+fn eta(elapsed_secs: f32, progress: f32) -> Option<u64> {
+    if progress >= 100.0 {
+        return Some(0);
+    }
+    if progress <= 0.0 {
+        return None;
+    }
+
+    Some((elapsed_secs * (100.0 - progress) / progress).round() as u64)
+}
 
-    pub fn emit(&self, window: &Window) {
-        window
-            .emit("scraping-progress", self)
-            .unwrap_or_else(|e| eprintln!("Failed to emit progress: {}", e));
+fn overall_progress(stages: &[StageProgress]) -> f32 {
+    let total_weight: f32 = stages.iter().map(|s| s.weight).sum();
+    if total_weight <= 0.0 {
+        return 0.0;
     }
+
+    stages.iter().map(|s| s.progress * s.weight).sum::<f32>() / total_weight
 }
 
-pub fn emit_progress(window: &Window, stage: &str, progress: f32, message: &str) {
-    let progress_data = ScrapingProgress::new(stage, progress, message);
-    progress_data.emit(window);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_is_weighted_and_forwarded_to_the_sink() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let tracker =
+            ProgressTracker::new(ChannelSink(sender), [("creatures", 3.0), ("icons", 1.0)]);
+
+        ScrapingProgress::new("creatures", 50.0, "Halfway").emit(&tracker);
+        ScrapingProgress::new("icons", 100.0, "Done").emit(&tracker);
+
+        let first = receiver.try_recv().unwrap();
+        assert_eq!((first.stage.as_str(), first.progress), ("creatures", 37.5));
+        let second = receiver.try_recv().unwrap();
+        assert_eq!(second.progress, 62.5);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn rapid_progress_updates_are_coalesced() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let tracker = ProgressTracker::new(ChannelSink(sender), [("icons", 1.0)]);
+
+        for line in 1..=2000 {
+            ScrapingProgress::new("icons", line as f32 / 20.0, "Processing icon")
+                .items(line)
+                .emit(&tracker);
+        }
+
+        let mut updates = Vec::new();
+        while let Ok(update) = receiver.try_recv() {
+            updates.push(update);
+        }
+
+        // The first update and the one reaching 100% always get through
+        assert!(updates.len() < 10, "{} updates sent", updates.len());
+        let last = updates.last().unwrap();
+        assert_eq!(last.progress, 100.0);
+        assert_eq!(last.stages[0].items, 2000);
+        assert_eq!(last.eta_secs, Some(0));
+    }
 }
@@ -0,0 +1,440 @@
+use super::checkpoint::Checkpoint;
+use super::common::ScraperClient;
+use super::keys::{insert_keyed, merge_collision, Keyed};
+use super::mods::ModMap;
+use super::progress::ProgressTracker;
+use super::report::{FailedFetch, KeyCollision, SkippedRow};
+use super::revisions::RevisionCache;
+use super::rules::{SkipRule, SkipRules};
+use super::{beacons, colors, creatures, engrams, icons, items};
+use crate::types::{ArkData, Beacon, Color, Creature, Edition, Engram, Icon, Item};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+pub type ScrapeResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Returned by a stage that failed only because the units it recorded as
+/// failed did, so retrying those units can recover it.
+#[derive(Debug)]
+pub struct UnitsFailed(pub String);
+
+impl std::fmt::Display for UnitsFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UnitsFailed {}
+
+/// The `ArkData` collection a scraper fills.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Creatures,
+    Items,
+    Engrams,
+    Beacons,
+    Colors,
+    Icons,
+}
+
+/// A single scraped row, tagged with the collection it belongs to.
+#[derive(Debug, Clone)]
+pub enum Entry {
+    Creature(Creature),
+    Item(Item),
+    Engram(Engram),
+    Beacon(Beacon),
+    Color(Color),
+    Icon(Icon),
+}
+
+impl From<Creature> for Entry {
+    fn from(value: Creature) -> Self {
+        Entry::Creature(value)
+    }
+}
+
+impl From<Item> for Entry {
+    fn from(value: Item) -> Self {
+        Entry::Item(value)
+    }
+}
+
+impl From<Engram> for Entry {
+    fn from(value: Engram) -> Self {
+        Entry::Engram(value)
+    }
+}
+
+impl From<Beacon> for Entry {
+    fn from(value: Beacon) -> Self {
+        Entry::Beacon(value)
+    }
+}
+
+impl From<Color> for Entry {
+    fn from(value: Color) -> Self {
+        Entry::Color(value)
+    }
+}
+
+impl From<Icon> for Entry {
+    fn from(value: Icon) -> Self {
+        Entry::Icon(value)
+    }
+}
+
+/// Collects the entries produced by one scraper, along with the rows it
+/// skipped, the fetches and units that failed and the keys that collided.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct ScrapeSink {
+    data: ArkData,
+    skipped: Vec<SkippedRow>,
+    failed_fetches: Vec<FailedFetch>,
+    #[serde(default)]
+    collisions: Vec<KeyCollision>,
+    #[serde(default)]
+    sections: Vec<String>,
+    #[serde(default)]
+    failed_units: Vec<String>,
+}
+
+impl ScrapeSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn skip(&mut self, name: &str, reason: impl Into<String>, source_url: &str) {
+        self.skipped.push(SkippedRow {
+            name: name.to_string(),
+            reason: reason.into(),
+            source_url: source_url.to_string(),
+            rule: None,
+        });
+    }
+
+    pub fn skip_by_rule(&mut self, name: &str, rule: &SkipRule, source_url: &str) {
+        self.skipped.push(SkippedRow {
+            name: name.to_string(),
+            reason: format!("matched skip rule \"{}\"", rule.id),
+            source_url: source_url.to_string(),
+            rule: Some(rule.id.clone()),
+        });
+    }
+
+    pub fn fetch_failed(&mut self, url: &str, error: &dyn std::fmt::Display) {
+        self.failed_fetches.push(FailedFetch {
+            url: url.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    /// Records a unit (item section, engram page) that yielded nothing, so
+    /// it can be retried on its own later.
+    pub fn unit_failed(&mut self, unit: &str) {
+        self.failed_units.push(unit.to_string());
+    }
+
+    pub fn skipped(&self) -> &[SkippedRow] {
+        &self.skipped
+    }
+
+    pub fn failed_fetches(&self) -> &[FailedFetch] {
+        &self.failed_fetches
+    }
+
+    pub fn failed_units(&self) -> &[String] {
+        &self.failed_units
+    }
+
+    pub fn collisions(&self) -> &[KeyCollision] {
+        &self.collisions
+    }
+
+    /// Records the wiki sections a scraper discovered and read.
+    pub fn set_sections(&mut self, sections: Vec<String>) {
+        self.sections = sections;
+    }
+
+    pub fn sections(&self) -> &[String] {
+        &self.sections
+    }
+
+    /// Stores `entry` under the key the [`super::keys`] policy gives it, or
+    /// under a suffixed key when a different entry already claims that one.
+    pub fn insert(&mut self, entry: impl Into<Entry>) {
+        let entry = entry.into();
+        let key = match &entry {
+            Entry::Creature(value) => value.key(),
+            Entry::Item(value) => value.key(),
+            Entry::Engram(value) => value.key(),
+            Entry::Beacon(value) => value.key(),
+            Entry::Color(value) => value.key(),
+            Entry::Icon(value) => value.key(),
+        };
+        self.insert_as(key, entry);
+    }
+
+    fn insert_as(&mut self, key: String, entry: Entry) {
+        let collisions = &mut self.collisions;
+        match entry {
+            Entry::Creature(value) => insert_keyed(
+                &mut self.data.creatures,
+                collisions,
+                Category::Creatures,
+                key,
+                value,
+            ),
+            Entry::Item(value) => insert_keyed(
+                &mut self.data.items,
+                collisions,
+                Category::Items,
+                key,
+                value,
+            ),
+            Entry::Engram(value) => insert_keyed(
+                &mut self.data.engrams,
+                collisions,
+                Category::Engrams,
+                key,
+                value,
+            ),
+            Entry::Beacon(value) => insert_keyed(
+                &mut self.data.beacons,
+                collisions,
+                Category::Beacons,
+                key,
+                value,
+            ),
+            Entry::Color(value) => insert_keyed(
+                &mut self.data.colors,
+                collisions,
+                Category::Colors,
+                key,
+                value,
+            ),
+            Entry::Icon(value) => insert_keyed(
+                &mut self.data.icons,
+                collisions,
+                Category::Icons,
+                key,
+                value,
+            ),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.creatures.len()
+            + self.data.items.len()
+            + self.data.engrams.len()
+            + self.data.beacons.len()
+            + self.data.colors.len()
+            + self.data.icons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves everything `other` collected into this sink, checking its
+    /// entries for key collisions with the ones already here.
+    pub fn absorb(&mut self, mut other: ScrapeSink) {
+        self.skipped.append(&mut other.skipped);
+        self.failed_fetches.append(&mut other.failed_fetches);
+        self.failed_units.append(&mut other.failed_units);
+
+        for collision in other.collisions {
+            let collisions = &mut self.collisions;
+            match collision.category {
+                Category::Creatures => {
+                    merge_collision(&mut self.data.creatures, collisions, collision)
+                }
+                Category::Items => merge_collision(&mut self.data.items, collisions, collision),
+                Category::Engrams => merge_collision(&mut self.data.engrams, collisions, collision),
+                Category::Beacons => merge_collision(&mut self.data.beacons, collisions, collision),
+                Category::Colors => merge_collision(&mut self.data.colors, collisions, collision),
+                Category::Icons => merge_collision(&mut self.data.icons, collisions, collision),
+            }
+        }
+
+        // Keep the keys `other` resolved, so its collisions stay consistent
+        let data = other.data;
+        for (key, value) in data.creatures {
+            self.insert_as(key, value.into());
+        }
+        for (key, value) in data.items {
+            self.insert_as(key, value.into());
+        }
+        for (key, value) in data.engrams {
+            self.insert_as(key, value.into());
+        }
+        for (key, value) in data.beacons {
+            self.insert_as(key, value.into());
+        }
+        for (key, value) in data.colors {
+            self.insert_as(key, value.into());
+        }
+        for (key, value) in data.icons {
+            self.insert_as(key, value.into());
+        }
+    }
+
+    /// Moves the collected entries into `target`.
+    pub fn drain_into(self, target: &mut ArkData) {
+        target.creatures.extend(self.data.creatures);
+        target.items.extend(self.data.items);
+        target.engrams.extend(self.data.engrams);
+        target.beacons.extend(self.data.beacons);
+        target.colors.extend(self.data.colors);
+        target.icons.extend(self.data.icons);
+    }
+}
+
+/// Everything a scraper needs from the surrounding run.
+pub struct ScrapeContext {
+    /// The `icons.txt` list the icons stage reads, if one was found.
+    pub icons_file: Option<PathBuf>,
+    pub client: ScraperClient,
+    pub progress: ProgressTracker,
+    pub checkpoint: Checkpoint,
+    pub rules: SkipRules,
+    /// Decides which mod each entry belongs to.
+    pub mods: ModMap,
+    /// What pages yielded at their last scraped revision.
+    pub revisions: RevisionCache,
+    /// The configured edition, used when an entry's row, section and mod
+    /// don't say which game it is from.
+    pub edition: Option<Edition>,
+}
+
+#[async_trait]
+pub trait Scraper: Send + Sync {
+    /// Stable identifier, also used as the progress stage name.
+    fn name(&self) -> &'static str;
+
+    fn category(&self) -> Category;
+
+    /// Share of the overall progress bar this scraper accounts for, relative
+    /// to the others. Fetching pages dominates a run, so this is roughly the
+    /// time the stage takes compared with one that reads a single page.
+    fn weight(&self) -> f32 {
+        1.0
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()>;
+
+    /// Scrapes only `units`, the failed units a previous run recorded.
+    /// Stages that don't work in units have nothing to retry.
+    async fn retry_units(
+        &self,
+        _ctx: &ScrapeContext,
+        _units: &[String],
+        _sink: &mut ScrapeSink,
+    ) -> ScrapeResult<()> {
+        Err(format!("{} cannot retry individual units", self.name()).into())
+    }
+}
+
+/// Describes a registered scraper to the UI.
+#[derive(Debug, Serialize, Clone)]
+pub struct ScraperInfo {
+    pub name: String,
+    pub category: Category,
+    pub weight: f32,
+}
+
+pub struct ScraperRegistry {
+    scrapers: Vec<Box<dyn Scraper>>,
+}
+
+impl ScraperRegistry {
+    pub fn new() -> Self {
+        Self {
+            scrapers: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, scraper: impl Scraper + 'static) {
+        self.scrapers.push(Box::new(scraper));
+    }
+
+    pub fn scrapers(&self) -> &[Box<dyn Scraper>] {
+        &self.scrapers
+    }
+
+    pub fn infos(&self) -> Vec<ScraperInfo> {
+        self.scrapers
+            .iter()
+            .map(|s| ScraperInfo {
+                name: s.name().to_string(),
+                category: s.category(),
+                weight: s.weight(),
+            })
+            .collect()
+    }
+}
+
+impl Default for ScraperRegistry {
+    /// The built-in wiki and resource scrapers, in run order.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(creatures::CreatureScraper);
+        registry.register(items::ItemScraper);
+        registry.register(engrams::EngramScraper);
+        registry.register(beacons::BeaconScraper);
+        registry.register(colors::ColorScraper);
+        registry.register(icons::IconScraper);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn colliding_keys_are_disambiguated_in_any_order() {
+        let island = test_support::item("PrimalItemResource_Stone");
+        let mod_stone = Item {
+            blueprint:
+                "Blueprint'/Game/Mods/Foo/PrimalItemResource_Stone.PrimalItemResource_Stone'"
+                    .to_string(),
+            ..island.clone()
+        };
+
+        let mut first = ScrapeSink::new();
+        first.insert(island);
+        let mut second = ScrapeSink::new();
+        second.insert(mod_stone.clone());
+        second.insert(mod_stone);
+
+        let mut forward = ScrapeSink::new();
+        forward.absorb(first.clone());
+        forward.absorb(second.clone());
+        let mut backward = ScrapeSink::new();
+        backward.absorb(second);
+        backward.absorb(first);
+
+        for sink in [&forward, &backward] {
+            assert_eq!(sink.collisions().len(), 1);
+            assert_eq!(sink.collisions()[0].key, "PrimalItemResource_Stone");
+        }
+
+        let keys = |sink: ScrapeSink| {
+            let mut data = ArkData::default();
+            sink.drain_into(&mut data);
+            let mut keys: Vec<_> = data.items.into_keys().collect();
+            keys.sort();
+            keys
+        };
+        let expected = vec![
+            "PrimalItemResource_Stone_Foo".to_string(),
+            "PrimalItemResource_Stone_Items".to_string(),
+        ];
+        assert_eq!(keys(forward), expected);
+        assert_eq!(keys(backward), expected);
+    }
+}
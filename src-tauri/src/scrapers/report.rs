@@ -0,0 +1,147 @@
+use super::registry::Category;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A table row or page a scraper saw but did not turn into an entry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedRow {
+    pub name: String,
+    pub reason: String,
+    pub source_url: String,
+    /// Id of the skip rule that excluded the row, if one did.
+    #[serde(default)]
+    pub rule: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FailedFetch {
+    pub url: String,
+    pub error: String,
+}
+
+/// Entries with different identities that were derived the same key.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyCollision {
+    pub category: Category,
+    pub key: String,
+    /// The keys the colliding entries were stored under instead.
+    pub resolved: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StageReport {
+    pub stage: String,
+    pub entries: usize,
+    pub skipped: Vec<SkippedRow>,
+    pub failed_fetches: Vec<FailedFetch>,
+    /// Units (item sections, engram pages) that yielded nothing and can be
+    /// retried without a full rerun.
+    #[serde(default)]
+    pub failed_units: Vec<String>,
+    #[serde(default)]
+    pub collisions: Vec<KeyCollision>,
+    /// Wiki sections the stage discovered and read.
+    #[serde(default)]
+    pub sections: Vec<String>,
+    /// Sections that were not there in the previous successful scrape.
+    #[serde(default)]
+    pub new_sections: Vec<String>,
+    /// Sections the previous successful scrape read that are gone now.
+    #[serde(default)]
+    pub vanished_sections: Vec<String>,
+    pub duration_ms: u64,
+    /// True when the stage output came from a checkpoint instead of the wiki.
+    pub restored: bool,
+    pub error: Option<String>,
+    /// True when `error` only came from the failed units, which makes the
+    /// stage recoverable by retrying them.
+    #[serde(default)]
+    pub failed_by_units: bool,
+}
+
+/// What happened during one `scrape_all` run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScrapeReport {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub duration_ms: u64,
+    pub stages: Vec<StageReport>,
+    /// The first stage error, if the run failed.
+    pub error: Option<String>,
+}
+
+impl StageReport {
+    /// Folds in a run that retried this stage's failed units. What still
+    /// failed replaces the earlier failures, which all came from those units.
+    /// Once nothing failed, a stage that failed only because of its units no
+    /// longer counts as failed; any other error, such as layout drift, stays.
+    /// The caller recounts `entries`, since the retry may have found entries
+    /// the stage already had.
+    pub fn merge_retry(&mut self, retry: StageReport) {
+        self.skipped.extend(retry.skipped);
+        self.failed_fetches = retry.failed_fetches;
+        self.failed_units = retry.failed_units;
+        self.collisions.extend(retry.collisions);
+        self.duration_ms += retry.duration_ms;
+
+        if self.failed_by_units && self.failed_units.is_empty() {
+            self.error = None;
+            self.failed_by_units = false;
+        }
+    }
+
+    /// Whether retrying the failed units can recover the stage: it has some,
+    /// and it either succeeded or failed because of them.
+    pub fn is_retryable(&self) -> bool {
+        !self.failed_units.is_empty() && (self.error.is_none() || self.failed_by_units)
+    }
+}
+
+impl ScrapeReport {
+    /// The first stage error, prefixed with the stage name.
+    pub fn first_error(stages: &[StageReport]) -> Option<String> {
+        stages
+            .iter()
+            .find_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.stage, e)))
+    }
+
+    /// Failed units per stage, for stages a retry can recover; see
+    /// [`StageReport::is_retryable`].
+    pub fn failed_units(&self) -> BTreeMap<String, Vec<String>> {
+        self.stages
+            .iter()
+            .filter(|s| s.is_retryable())
+            .map(|s| (s.stage.clone(), s.failed_units.clone()))
+            .collect()
+    }
+}
+
+/// Short form of a persisted report for listing past runs.
+#[derive(Debug, Serialize, Clone)]
+pub struct ScrapeReportSummary {
+    pub id: String,
+    pub started_at: i64,
+    pub duration_ms: u64,
+    pub entries: usize,
+    pub skipped: usize,
+    pub failed_fetches: usize,
+    pub failed_units: usize,
+    pub collisions: usize,
+    pub error: Option<String>,
+}
+
+impl ScrapeReportSummary {
+    pub fn new(id: String, report: &ScrapeReport) -> Self {
+        Self {
+            id,
+            started_at: report.started_at,
+            duration_ms: report.duration_ms,
+            entries: report.stages.iter().map(|s| s.entries).sum(),
+            skipped: report.stages.iter().map(|s| s.skipped.len()).sum(),
+            failed_fetches: report.stages.iter().map(|s| s.failed_fetches.len()).sum(),
+            failed_units: report.stages.iter().map(|s| s.failed_units.len()).sum(),
+            collisions: report.stages.iter().map(|s| s.collisions.len()).sum(),
+            error: report.error.clone(),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Remembers what each wiki page yielded at a given revision, so a later run
+//! only refetches pages edited since.
+
+use super::common::ScraperClient;
+use super::keys::fnv1a;
+use super::registry::{ScrapeResult, ScrapeSink};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The API accepts at most this many titles per query.
+const TITLES_PER_QUERY: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CachedPage {
+    revision: u64,
+    /// [`fingerprint`] of the settings and lookups the page was parsed with.
+    #[serde(default)]
+    inputs: u64,
+    sink: ScrapeSink,
+}
+
+/// Output of previously scraped pages, keyed by URL, with the revision each
+/// was scraped at.
+pub struct RevisionCache {
+    path: Option<PathBuf>,
+    /// Whether [`commit`](Self::commit) writes the cache back to `path`.
+    writable: bool,
+    pages: Mutex<HashMap<String, CachedPage>>,
+    /// Pages scraped this run per stage, kept apart until the stage succeeds.
+    fresh: Mutex<HashMap<String, HashMap<String, CachedPage>>>,
+}
+
+/// Identifies what a page's output depends on besides the page itself, such
+/// as skip rules and the mod map, so changing them invalidates the cache.
+pub fn fingerprint(inputs: &impl Serialize) -> u64 {
+    let json = serde_json::to_string(inputs).unwrap_or_default();
+    fnv1a(&json)
+}
+
+impl RevisionCache {
+    /// A cache that holds nothing, so every page is fetched.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            writable: false,
+            pages: Mutex::new(HashMap::new()),
+            fresh: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads the cache at `path`. A missing or unreadable file starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        let pages = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(pages) => Some(pages),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Ignoring unreadable revision cache");
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            writable: true,
+            pages: Mutex::new(pages),
+            fresh: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads the cache at `path` for lookups only, for runs that must not
+    /// leave anything behind.
+    pub fn load_read_only(path: PathBuf) -> Self {
+        Self {
+            writable: false,
+            ..Self::load(path)
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// What `url` yielded last time, if the page is still at `revision` and
+    /// was parsed with the same `inputs`.
+    pub fn get(&self, url: &str, revision: u64, inputs: u64) -> Option<ScrapeSink> {
+        let pages = self.pages.lock().unwrap();
+        pages
+            .get(url)
+            .filter(|page| page.revision == revision && page.inputs == inputs)
+            .map(|page| page.sink.clone())
+    }
+
+    /// Records what `url` yielded for `stage`. It is only reused once
+    /// [`commit`](Self::commit) confirms the stage succeeded.
+    pub fn put(&self, stage: &str, url: &str, revision: u64, inputs: u64, sink: &ScrapeSink) {
+        self.fresh
+            .lock()
+            .unwrap()
+            .entry(stage.to_string())
+            .or_default()
+            .insert(
+                url.to_string(),
+                CachedPage {
+                    revision,
+                    inputs,
+                    sink: sink.clone(),
+                },
+            );
+    }
+
+    /// Forgets pages that are no longer linked from the wiki.
+    pub fn retain(&self, urls: &[String]) {
+        let urls: HashSet<&String> = urls.iter().collect();
+        self.pages
+            .lock()
+            .unwrap()
+            .retain(|url, _| urls.contains(url));
+    }
+
+    /// Keeps the pages `stage` scraped this run and writes the cache to disk;
+    /// call once the stage has succeeded.
+    pub fn commit(&self, stage: &str) {
+        let fresh = self.fresh.lock().unwrap().remove(stage);
+        if let Some(fresh) = fresh {
+            self.pages.lock().unwrap().extend(fresh);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = match &self.path {
+            Some(path) if self.writable => path,
+            _ => return,
+        };
+
+        let json = {
+            let pages = self.pages.lock().unwrap();
+            serde_json::to_string(&*pages)
+        };
+
+        match json {
+            Ok(json) => {
+                if let Err(e) = fs::write(path, json) {
+                    tracing::error!(error = %e, "Failed to write revision cache");
+                }
+            }
+            Err(e) => tracing::error!(error = %e, "Failed to serialize revision cache"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct InfoResponse {
+    #[serde(default)]
+    query: InfoQuery,
+}
+
+#[derive(Deserialize, Default)]
+struct InfoQuery {
+    #[serde(default)]
+    normalized: Vec<Normalized>,
+    #[serde(default)]
+    pages: Vec<PageInfo>,
+}
+
+#[derive(Deserialize)]
+struct Normalized {
+    from: String,
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct PageInfo {
+    title: String,
+    lastrevid: Option<u64>,
+}
+
+/// The current revision ID of each wiki page in `urls`, keyed by URL. Pages
+/// the wiki does not know are left out.
+pub async fn fetch_revisions(
+    client: &ScraperClient,
+    urls: &[String],
+) -> ScrapeResult<HashMap<String, u64>> {
+    let titled: Vec<(String, &String)> = urls
+        .iter()
+        .filter_map(|url| page_title(url).map(|title| (title, url)))
+        .collect();
+
+    let mut revisions = HashMap::new();
+    for batch in titled.chunks(TITLES_PER_QUERY) {
+        let titles = batch
+            .iter()
+            .map(|(title, _)| title.as_str())
+            .collect::<Vec<_>>()
+            .join("|");
+        let params = [
+            ("action", "query"),
+            ("prop", "info"),
+            ("titles", titles.as_str()),
+            ("format", "json"),
+            ("formatversion", "2"),
+        ];
+
+        let url = Url::parse_with_params(&client.url("/api.php"), &params)?;
+        let response: InfoResponse = serde_json::from_str(&client.fetch_page(url.as_str()).await?)?;
+
+        let normalized: HashMap<&str, &str> = response
+            .query
+            .normalized
+            .iter()
+            .map(|n| (n.from.as_str(), n.to.as_str()))
+            .collect();
+        let current: HashMap<&str, u64> = response
+            .query
+            .pages
+            .iter()
+            .filter_map(|page| page.lastrevid.map(|rev| (page.title.as_str(), rev)))
+            .collect();
+
+        for (title, url) in batch {
+            let title = normalized.get(title.as_str()).copied().unwrap_or(title);
+            if let Some(revision) = current.get(title) {
+                revisions.insert(url.to_string(), *revision);
+            }
+        }
+    }
+
+    Ok(revisions)
+}
+
+/// The page title a `/wiki/…` URL points at, e.g. `Stone Pick` for
+/// `https://ark.wiki.gg/wiki/Stone_Pick`.
+pub fn page_title(url: &str) -> Option<String> {
+    let (_, path) = url.split_once("/wiki/")?;
+    let path = path.split(['#', '?']).next()?;
+    if path.is_empty() {
+        return None;
+    }
+
+    Some(percent_decode(path).replace('_', " "))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn cached_pages_are_reused_only_at_the_same_revision() {
+        let url = "https://ark.wiki.gg/wiki/Stone_Pick";
+        assert_eq!(page_title(url).as_deref(), Some("Stone Pick"));
+        assert_eq!(
+            page_title("https://ark.wiki.gg/wiki/Tek_Replicator%27s_Core#Engram").as_deref(),
+            Some("Tek Replicator's Core")
+        );
+
+        let path = env::temp_dir().join(format!("engram_revisions_{}.json", std::process::id()));
+        let cache = RevisionCache::load(path.clone());
+        let mut page = ScrapeSink::new();
+        page.skip("Stone Pick", "page has no title", url);
+        cache.put("engrams", url, 41, 7, &page);
+        assert!(
+            cache.get(url, 41, 7).is_none(),
+            "kept before the stage succeeded"
+        );
+        cache.commit("engrams");
+
+        // A preview reads the cache but leaves it as it was
+        let preview = RevisionCache::load_read_only(path.clone());
+        assert!(preview.get(url, 41, 7).is_some());
+        preview.put("engrams", url, 42, 7, &page);
+        preview.commit("engrams");
+
+        let reloaded = RevisionCache::load(path.clone());
+        fs::remove_file(&path).ok();
+        assert_eq!(
+            reloaded.get(url, 41, 7).map(|sink| sink.skipped().len()),
+            Some(1)
+        );
+        assert!(reloaded.get(url, 42, 7).is_none());
+        // Parsed with other rules or lookups
+        assert!(reloaded.get(url, 41, 8).is_none());
+
+        reloaded.retain(&[]);
+        assert!(reloaded.get(url, 41, 7).is_none());
+    }
+}
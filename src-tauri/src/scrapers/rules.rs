@@ -0,0 +1,321 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Which part of a scraped row a skip rule looks at.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleField {
+    Name,
+    Blueprint,
+    Mod,
+}
+
+impl Default for RuleField {
+    fn default() -> Self {
+        RuleField::Name
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    Contains,
+    Regex,
+}
+
+/// One entry of the user-editable skip rules file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkipRule {
+    /// Named in the scrape report for every row the rule excludes.
+    pub id: String,
+    /// Scraper stages the rule applies to; empty means every stage.
+    #[serde(default)]
+    pub stages: Vec<String>,
+    #[serde(default)]
+    pub field: RuleField,
+    #[serde(rename = "match")]
+    pub kind: MatchKind,
+    pub pattern: String,
+}
+
+impl SkipRule {
+    fn new(id: &str, stage: &str, kind: MatchKind, pattern: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            stages: vec![stage.to_string()],
+            field: RuleField::Name,
+            kind,
+            pattern: pattern.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkipRuleSet {
+    pub rules: Vec<SkipRule>,
+}
+
+impl Default for SkipRuleSet {
+    fn default() -> Self {
+        use MatchKind::*;
+
+        // Event, boss-summon and variant creatures share names with the
+        // regular ones, so match them as whole words or prefixes.
+        let word = |word: &str| format!(r"\b{}\b", word);
+        // Item and engram names are compared as the wiki displays them, with
+        // spaces; beacon names keep the underscores of their class names.
+        let rules = vec![
+            SkipRule::new("creature-alpha", "creatures", Prefix, "Alpha "),
+            SkipRule::new("creature-vr", "creatures", Regex, &word("VR")),
+            SkipRule::new(
+                "creature-malfunctioned",
+                "creatures",
+                Prefix,
+                "Malfunctioned ",
+            ),
+            SkipRule::new("creature-retrieve", "creatures", Regex, &word("Retrieve")),
+            SkipRule::new("creature-escort", "creatures", Regex, &word("Escort")),
+            SkipRule::new("creature-minion", "creatures", Regex, &word("Minions?")),
+            SkipRule::new("creature-race", "creatures", Regex, &word("Race")),
+            SkipRule::new("creature-sport", "creatures", Regex, &word("Sport")),
+            SkipRule::new("creature-summoned", "creatures", Regex, &word("Summoned")),
+            SkipRule::new("creature-hunt", "creatures", Regex, &word("Hunt")),
+            SkipRule::new("creature-mashup", "creatures", Regex, &word("Mashup")),
+            SkipRule::new("item-undefined", "items", Contains, "undefined"),
+            SkipRule::new("item-null", "items", Contains, "null"),
+            SkipRule::new("item-platform-cart", "items", Contains, "Platform Cart"),
+            SkipRule::new("engram-platform-cart", "engrams", Contains, "Platform Cart"),
+            SkipRule::new("beacon-genesis-2", "beacons", Contains, "Genesis_2_"),
+            SkipRule::new(
+                "beacon-genesis-1-fishing",
+                "beacons",
+                Contains,
+                "Genesis_1_Fishing",
+            ),
+            SkipRule::new(
+                "beacon-choose-your-own-adventure",
+                "beacons",
+                Contains,
+                "Choose_Your_Own_Adventure",
+            ),
+            SkipRule::new(
+                "beacon-team-downriver-run",
+                "beacons",
+                Contains,
+                "Team_Downriver_Run",
+            ),
+            SkipRule::new(
+                "beacon-starwing-strike",
+                "beacons",
+                Contains,
+                "Starwing_Strike",
+            ),
+            SkipRule::new("beacon-star-dolphin", "beacons", Contains, "Star_Dolphin"),
+            SkipRule::new(
+                "beacon-slipstream-sweep",
+                "beacons",
+                Contains,
+                "Slipstream_Sweep",
+            ),
+            SkipRule::new(
+                "beacon-slide-and-glide",
+                "beacons",
+                Contains,
+                "Slide_and_Glide",
+            ),
+        ];
+
+        Self { rules }
+    }
+}
+
+/// The fields of a row that rules can target. Rules on a field the scraper
+/// could not extract never match.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RowFields<'a> {
+    pub name: &'a str,
+    pub blueprint: Option<&'a str>,
+    pub mod_id: Option<&'a str>,
+}
+
+impl<'a> RowFields<'a> {
+    fn get(&self, field: RuleField) -> Option<&'a str> {
+        match field {
+            RuleField::Name => Some(self.name),
+            RuleField::Blueprint => self.blueprint,
+            RuleField::Mod => self.mod_id,
+        }
+    }
+}
+
+enum Matcher {
+    Exact(String),
+    Prefix(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Matcher::Exact(pattern) => value == pattern,
+            Matcher::Prefix(pattern) => value.starts_with(pattern.as_str()),
+            Matcher::Contains(pattern) => value.contains(pattern.as_str()),
+            Matcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// A [`SkipRuleSet`] with its patterns compiled, ready to test rows against.
+#[derive(Default)]
+pub struct SkipRules {
+    rules: Vec<(SkipRule, Matcher)>,
+}
+
+impl SkipRules {
+    pub fn compile(set: &SkipRuleSet) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(set.rules.len());
+
+        for rule in &set.rules {
+            let matcher = match rule.kind {
+                MatchKind::Exact => Matcher::Exact(rule.pattern.clone()),
+                MatchKind::Prefix => Matcher::Prefix(rule.pattern.clone()),
+                MatchKind::Contains => Matcher::Contains(rule.pattern.clone()),
+                MatchKind::Regex => Matcher::Regex(
+                    Regex::new(&rule.pattern)
+                        .map_err(|e| format!("Invalid pattern in skip rule {}: {}", rule.id, e))?,
+                ),
+            };
+            rules.push((rule.clone(), matcher));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// The rules in the order they are tried.
+    pub fn rules(&self) -> impl Iterator<Item = &SkipRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// Returns the first rule for `stage` that matches `row`, if any.
+    pub fn matching(&self, stage: &str, row: &RowFields) -> Option<&SkipRule> {
+        self.rules
+            .iter()
+            .filter(|(rule, _)| rule.stages.is_empty() || rule.stages.iter().any(|s| s == stage))
+            .find(|(rule, matcher)| matches!(row.get(rule.field), Some(v) if matcher.is_match(v)))
+            .map(|(rule, _)| rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::common::clean_name;
+
+    #[test]
+    fn default_skip_rules_match_whole_words() {
+        let rules = SkipRules::compile(&SkipRuleSet::default()).unwrap();
+        let skipped_by = |name: &str| {
+            let row = RowFields {
+                name,
+                ..RowFields::default()
+            };
+            rules
+                .matching("creatures", &row)
+                .map(|rule| rule.id.clone())
+        };
+
+        assert_eq!(
+            skipped_by("Alpha Raptor").as_deref(),
+            Some("creature-alpha")
+        );
+        assert_eq!(
+            skipped_by("Tek Stryder Race").as_deref(),
+            Some("creature-race")
+        );
+        assert_eq!(skipped_by("Alphadon"), None);
+        assert_eq!(skipped_by("Bracer"), None);
+        assert_eq!(skipped_by("Raptor"), None);
+    }
+
+    #[test]
+    fn default_skip_rules_match_display_names() {
+        let rules = SkipRules::compile(&SkipRuleSet::default()).unwrap();
+        let skipped_by = |stage: &str, name: &str| {
+            let row = RowFields {
+                name,
+                ..RowFields::default()
+            };
+            rules.matching(stage, &row).map(|rule| rule.id.clone())
+        };
+
+        assert_eq!(
+            skipped_by("engrams", "Platform Cart").as_deref(),
+            Some("engram-platform-cart")
+        );
+        assert_eq!(
+            skipped_by("beacons", "Star_Dolphin Crate").as_deref(),
+            Some("beacon-star-dolphin")
+        );
+        assert_eq!(
+            skipped_by("items", "undefined (2)").as_deref(),
+            Some("item-undefined")
+        );
+        assert_eq!(skipped_by("items", "Beer Barrel"), None);
+    }
+
+    #[test]
+    fn every_default_skip_rule_matches_a_scraped_name() {
+        // Titles as the wiki tables give them; scrapers pass them through
+        // `clean_name` before the rules see them
+        let titles = [
+            ("creatures", "Alpha Raptor"),
+            ("creatures", "VR Rex"),
+            ("creatures", "Malfunctioned Rockwell"),
+            ("creatures", "Bulbdog Retrieve"),
+            ("creatures", "Otter Escort"),
+            ("creatures", "Rockwell Minion"),
+            ("creatures", "Tek Stryder Race"),
+            ("creatures", "Dodo Sport"),
+            ("creatures", "Summoned Drake"),
+            ("creatures", "Deinonychus Hunt"),
+            ("creatures", "Gen2 Mashup Rex"),
+            ("items", "undefined"),
+            ("items", "null"),
+            ("items", "Platform Cart"),
+            ("engrams", "Platform Cart (Gen2)"),
+            ("beacons", "Genesis_2_Loot_Crate"),
+            ("beacons", "Genesis_1_Fishing_Bonus"),
+            ("beacons", "Choose_Your_Own_Adventure_Crate"),
+            ("beacons", "Team_Downriver_Run_Crate"),
+            ("beacons", "Starwing_Strike_Crate"),
+            ("beacons", "Star_Dolphin_Crate"),
+            ("beacons", "Slipstream_Sweep_Crate"),
+            ("beacons", "Slide_and_Glide_Crate"),
+        ];
+
+        let set = SkipRuleSet::default();
+        let rules = SkipRules::compile(&set).unwrap();
+        let matched: Vec<String> = titles
+            .iter()
+            .filter_map(|(stage, title)| {
+                let name = clean_name(title);
+                let row = RowFields {
+                    name: &name,
+                    ..RowFields::default()
+                };
+                rules.matching(stage, &row).map(|rule| rule.id.clone())
+            })
+            .collect();
+
+        for rule in &set.rules {
+            assert!(
+                matched.contains(&rule.id),
+                "{} matches no scraped name",
+                rule.id
+            );
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Golden-output tests that replay recorded wiki pages from `tests/fixtures/wiki`,
+//! and tests of whole runs through [`scrape_all`](super::scrape_all).
+//!
+//! Run with `UPDATE_GOLDEN=1` to accept new output after an intended change.
+//! To refresh the fixtures themselves, set the scraper config's `fixtures` to
+//! `{ "mode": "record", "dir": "<path>" }` and run a scrape from the app.
+
+use super::checkpoint::Checkpoint;
+use super::common::ScraperClient;
+use super::config::ScraperConfig;
+use super::fixtures::FixtureMode;
+use super::layout::Baseline;
+use super::mods::ModMap;
+use super::progress::{NoopSink, ProgressTracker};
+use super::registry::{
+    Category, ScrapeContext, ScrapeResult, ScrapeSink, Scraper, ScraperRegistry,
+};
+use super::revisions::RevisionCache;
+use super::rules::{SkipRuleSet, SkipRules};
+use super::{beacons, colors, creatures, engrams, items, ScrapeInputs, ScrapeOutcome};
+use crate::test_support;
+use crate::types::ArkData;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+fn tests_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+fn replay_context() -> ScrapeContext {
+    let config = ScraperConfig {
+        fixtures: FixtureMode::Replay {
+            dir: tests_dir().join("fixtures").join("wiki"),
+        },
+        ..ScraperConfig::default()
+    };
+
+    ScrapeContext {
+        icons_file: None,
+        client: ScraperClient::new(&config).expect("failed to build replay client"),
+        progress: ProgressTracker::new(NoopSink, []),
+        checkpoint: Checkpoint::disabled(),
+        rules: SkipRules::compile(&SkipRuleSet::default()).expect("default skip rules compile"),
+        mods: ModMap::default(),
+        revisions: RevisionCache::disabled(),
+        edition: None,
+    }
+}
+
+fn collect(sink: ScrapeSink) -> ArkData {
+    let mut data = ArkData::default();
+    sink.drain_into(&mut data);
+    data
+}
+
+fn assert_golden<T: Serialize>(name: &str, entries: &HashMap<String, T>) {
+    let sorted: BTreeMap<_, _> = entries.iter().collect();
+    let actual = serde_json::to_string_pretty(&sorted).unwrap() + "\n";
+    let golden_path = tests_dir().join("golden").join(format!("{}.json", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        fs::write(&golden_path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&golden_path).unwrap_or_else(|e| {
+        panic!(
+            "missing golden file {} ({}); run with UPDATE_GOLDEN=1 to create it",
+            golden_path.display(),
+            e
+        )
+    });
+
+    assert_eq!(
+        expected,
+        actual,
+        "{} output differs from {}; run with UPDATE_GOLDEN=1 to accept",
+        name,
+        golden_path.display()
+    );
+}
+
+#[tokio::test]
+async fn creatures_match_golden() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    creatures::scrape_creatures(&ctx, &mut sink).await.unwrap();
+    assert_golden("creatures", &collect(sink).creatures);
+}
+
+#[tokio::test]
+async fn items_match_golden() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    items::scrape_items(&ctx, &mut sink).await.unwrap();
+    assert_golden("items", &collect(sink).items);
+}
+
+#[tokio::test]
+async fn engrams_match_golden() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    engrams::scrape_engrams(&ctx, &mut sink).await.unwrap();
+    assert_golden("engrams", &collect(sink).engrams);
+}
+
+#[tokio::test]
+async fn beacons_match_golden() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    beacons::scrape_beacons(&ctx, &mut sink).await.unwrap();
+    assert_golden("beacons", &collect(sink).beacons);
+}
+
+#[tokio::test]
+async fn colors_match_golden() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    colors::scrape_colors(&ctx, &mut sink).await.unwrap();
+    assert_golden("colors", &collect(sink).colors);
+}
+
+#[tokio::test]
+async fn retrying_item_sections_records_the_ones_still_failing() {
+    let ctx = replay_context();
+    let mut sink = ScrapeSink::new();
+    let units = ["Resources".to_string(), "Not A Section".to_string()];
+    items::ItemScraper
+        .retry_units(&ctx, &units, &mut sink)
+        .await
+        .unwrap();
+
+    assert_eq!(sink.failed_units(), ["Not A Section"]);
+    assert_eq!(sink.failed_fetches().len(), 1);
+
+    let data = collect(sink);
+    assert!(!data.items.is_empty());
+}
+
+#[test]
+fn a_retry_that_recovers_every_unit_clears_the_stage_error() {
+    let outcome = |items: &[&str], failed_units: &[&str], error: Option<&str>, by_units: bool| {
+        let mut data = ArkData::default();
+        for name in items {
+            data.items
+                .insert(name.to_string(), test_support::item(name));
+        }
+        let stage = serde_json::json!({
+            "stage": "items",
+            "entries": items.len(),
+            "skipped": [],
+            "failed_fetches": [],
+            "failed_units": failed_units,
+            "duration_ms": 0,
+            "restored": false,
+            "error": error,
+            "failed_by_units": by_units,
+        });
+        ScrapeOutcome {
+            data,
+            report: serde_json::from_value(serde_json::json!({
+                "started_at": 0,
+                "finished_at": 0,
+                "duration_ms": 0,
+                "stages": [stage],
+                "error": error.map(|e| format!("items: {}", e)),
+            }))
+            .unwrap(),
+        }
+    };
+    let no_items = Some("No items were successfully scraped");
+
+    let mut partial = outcome(&[], &["Resources"], no_items, true);
+    partial.merge_retry(outcome(&["Stone"], &["Resources"], None, false));
+    assert!(partial.report.error.is_some());
+
+    let mut failed = outcome(&["Stone"], &["Resources"], no_items, true);
+    assert_eq!(failed.report.failed_units().len(), 1);
+    failed.merge_retry(outcome(&["Stone", "Wood"], &[], None, false));
+    assert_eq!(failed.report.error, None);
+    assert_eq!(failed.report.stages[0].error, None);
+    // Stone was found by both runs but is only counted once
+    assert_eq!(failed.report.stages[0].entries, 2);
+}
+
+#[test]
+fn a_retry_does_not_clear_layout_drift() {
+    let drift = "wiki layout changed at Item_IDs: expected at least 1 item tables, found 0";
+    let mut drifted: ScrapeOutcome = serde_json::from_value(serde_json::json!({
+        "data": ArkData::default(),
+        "report": {
+            "started_at": 0,
+            "finished_at": 0,
+            "duration_ms": 0,
+            "stages": [{
+                "stage": "items",
+                "entries": 0,
+                "skipped": [],
+                "failed_fetches": [],
+                "failed_units": ["Resources"],
+                "duration_ms": 0,
+                "restored": false,
+                "error": drift,
+            }],
+            "error": format!("items: {}", drift),
+        },
+    }))
+    .unwrap();
+    // The data went with the stage, so there is nothing to retry into
+    assert!(drifted.report.failed_units().is_empty());
+
+    let mut recovered = drifted.clone();
+    recovered.report.stages[0].error = None;
+    recovered.report.stages[0].failed_units.clear();
+    recovered.report.error = None;
+    recovered
+        .data
+        .items
+        .insert("Stone".to_string(), test_support::item("Stone"));
+
+    drifted.merge_retry(recovered);
+    assert_eq!(drifted.report.stages[0].error.as_deref(), Some(drift));
+    assert!(drifted.report.error.is_some());
+}
+
+const UNITS: [&str; 3] = ["Stone", "Wood", "Flint"];
+
+/// Scrapes one item per unit of [`UNITS`], restoring units the checkpoint
+/// holds, and fails at `fail_at`. Records the units it actually scraped.
+struct UnitScraper {
+    fail_at: Option<&'static str>,
+    scraped: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Scraper for UnitScraper {
+    fn name(&self) -> &'static str {
+        "items"
+    }
+
+    fn category(&self) -> Category {
+        Category::Items
+    }
+
+    async fn scrape(&self, ctx: &ScrapeContext, sink: &mut ScrapeSink) -> ScrapeResult<()> {
+        for unit in UNITS {
+            if let Some(restored) = ctx.checkpoint.completed_unit("items", unit) {
+                sink.absorb(restored);
+                continue;
+            }
+
+            self.scraped.lock().unwrap().push(unit.to_string());
+            if self.fail_at == Some(unit) {
+                return Err(format!("Failed to scrape {}", unit).into());
+            }
+
+            let mut page = ScrapeSink::new();
+            page.insert(test_support::item(unit));
+            ctx.checkpoint.complete_unit("items", unit, &page);
+            sink.absorb(page);
+        }
+        Ok(())
+    }
+}
+
+async fn scrape_units(scraper: UnitScraper, checkpoint: Checkpoint) -> ScrapeOutcome {
+    let mut registry = ScraperRegistry::new();
+    registry.register(scraper);
+    let inputs = ScrapeInputs {
+        checkpoint,
+        rules: SkipRules::compile(&SkipRuleSet::default()).unwrap(),
+        mods: ModMap::default(),
+        baseline: Baseline::new(0.5),
+        icons_file: None,
+        revisions: RevisionCache::disabled(),
+        edition: None,
+        retry: None,
+    };
+    let client = ScraperClient::new(&ScraperConfig::default()).unwrap();
+    super::scrape_all(NoopSink, client, &registry, inputs).await
+}
+
+#[tokio::test]
+async fn an_interrupted_scrape_resumes_after_its_completed_units() {
+    let path = env::temp_dir().join(format!("scrape_checkpoint_{}.json", std::process::id()));
+    let scraped = Arc::new(Mutex::new(Vec::new()));
+    let scraper = |fail_at| UnitScraper {
+        fail_at,
+        scraped: scraped.clone(),
+    };
+
+    let interrupted = scrape_units(scraper(Some("Flint")), Checkpoint::start(path.clone())).await;
+    assert!(interrupted.report.error.is_some());
+    assert!(path.exists(), "kept for the next run");
+
+    scraped.lock().unwrap().clear();
+    let resumed = scrape_units(scraper(None), Checkpoint::resume(path.clone()).unwrap()).await;
+    assert!(resumed.report.error.is_none());
+    assert_eq!(*scraped.lock().unwrap(), ["Flint"]);
+    assert_eq!(resumed.data.items.len(), UNITS.len());
+    assert!(!path.exists(), "cleared once every stage succeeded");
+}
@@ -0,0 +1,16 @@
+//! Fixtures shared by the unit tests of several modules.
+
+use crate::types::Item;
+
+/// A base-game resource named `name`, with a blueprint and class name
+/// derived from it.
+pub fn item(name: &str) -> Item {
+    Item {
+        type_name: "Resource".to_string(),
+        name: name.to_string(),
+        mod_id: "ark".to_string(),
+        editions: Vec::new(),
+        class_name: format!("PrimalItemResource_{}_C", name),
+        blueprint: format!("Blueprint'/Game/Items/{0}.{0}'", name),
+    }
+}
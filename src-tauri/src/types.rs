@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod blueprint;
+pub mod settings;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ArkData {
     pub creatures: HashMap<String, Creature>,
@@ -9,6 +12,9 @@ pub struct ArkData {
     pub beacons: HashMap<String, Beacon>,
     pub colors: HashMap<String, Color>,
     pub icons: HashMap<String, Icon>,
+    /// Mods and DLCs that entries reference by `mod_id`, keyed by ID.
+    #[serde(default)]
+    pub mods: HashMap<String, Mod>,
     pub version: String,
     pub last_updated: i64,
 }
@@ -17,7 +23,12 @@ pub struct ArkData {
 pub struct Creature {
     pub type_name: String,
     pub name: String,
-    pub mod_name: String,
+    /// Key of the entry's mod in [`ArkData::mods`].
+    #[serde(alias = "mod_name")]
+    pub mod_id: String,
+    /// Games the entry exists in; empty when that is not known.
+    #[serde(default)]
+    pub editions: Vec<Edition>,
     pub entity_id: String,
     pub blueprint: String,
 }
@@ -26,7 +37,12 @@ pub struct Creature {
 pub struct Item {
     pub type_name: String,
     pub name: String,
-    pub mod_name: String,
+    /// Key of the entry's mod in [`ArkData::mods`].
+    #[serde(alias = "mod_name")]
+    pub mod_id: String,
+    /// Games the entry exists in; empty when that is not known.
+    #[serde(default)]
+    pub editions: Vec<Edition>,
     pub class_name: String,
     pub blueprint: String,
 }
@@ -35,7 +51,12 @@ pub struct Item {
 pub struct Engram {
     pub type_name: String,
     pub name: String,
-    pub mod_name: String,
+    /// Key of the entry's mod in [`ArkData::mods`].
+    #[serde(alias = "mod_name")]
+    pub mod_id: String,
+    /// Games the entry exists in; empty when that is not known.
+    #[serde(default)]
+    pub editions: Vec<Edition>,
     pub blueprint: String,
     pub class_name: String,
 }
@@ -44,7 +65,12 @@ pub struct Engram {
 pub struct Beacon {
     pub type_name: String,
     pub name: String,
-    pub mod_name: String,
+    /// Key of the entry's mod in [`ArkData::mods`].
+    #[serde(alias = "mod_name")]
+    pub mod_id: String,
+    /// Games the entry exists in; empty when that is not known.
+    #[serde(default)]
+    pub editions: Vec<Edition>,
     pub class_name: String,
 }
 
@@ -56,6 +82,77 @@ pub struct Color {
     pub hex_code: String,
 }
 
+/// A mod or DLC entries can belong to. Data from before the registry has
+/// free-text `mod_name`s in place of IDs until it is migrated.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Mod {
+    pub id: String,
+    /// Name shown in the UI.
+    pub name: String,
+    /// Steam or CurseForge workshop ID; official content has none.
+    #[serde(default)]
+    pub workshop_id: Option<String>,
+    /// Where the mod's blueprints live, e.g. `/Game/ScorchedEarth/`.
+    #[serde(default)]
+    pub blueprint_prefix: Option<String>,
+    /// True for content published by the game's developers.
+    #[serde(default)]
+    pub official: bool,
+    /// Games the mod is available for; empty for both.
+    #[serde(default)]
+    pub editions: Vec<Edition>,
+}
+
+/// The game an entry belongs to. Blueprint paths, spawn syntax and content
+/// differ between the two.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Edition {
+    /// ARK: Survival Evolved
+    Ase,
+    /// ARK: Survival Ascended
+    Asa,
+}
+
+impl Edition {
+    /// The editions `text` mentions, such as a wiki column reading
+    /// "ASA only" or a section titled "Survival Ascended", in order.
+    pub fn mentioned_in(text: &str) -> Vec<Edition> {
+        let text = text.to_lowercase();
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        let mut editions = Vec::new();
+        if words.iter().any(|w| *w == "ase" || *w == "evolved") {
+            editions.push(Edition::Ase);
+        }
+        if words.iter().any(|w| *w == "asa" || *w == "ascended") {
+            editions.push(Edition::Asa);
+        }
+        editions
+    }
+
+    /// Whether an entry tagged with `editions` belongs to this edition.
+    /// Untagged entries belong to both.
+    pub fn includes(self, editions: &[Edition]) -> bool {
+        editions.is_empty() || editions.contains(&self)
+    }
+}
+
+impl std::str::FromStr for Edition {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "ase" => Ok(Edition::Ase),
+            "asa" => Ok(Edition::Asa),
+            _ => Err(format!("Unknown edition '{}', expected ase or asa", value)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Icon {
     pub type_name: String,
@@ -63,6 +160,44 @@ pub struct Icon {
     pub path: String,
 }
 
+impl ArkData {
+    /// Drops the entries and mods that don't belong to `edition`. Colors and
+    /// icons are shared by both games and kept.
+    pub fn retain_edition(&mut self, edition: Edition) {
+        self.creatures.retain(|_, c| edition.includes(&c.editions));
+        self.items.retain(|_, i| edition.includes(&i.editions));
+        self.engrams.retain(|_, e| edition.includes(&e.editions));
+        self.beacons.retain(|_, b| edition.includes(&b.editions));
+        self.mods.retain(|_, m| edition.includes(&m.editions));
+    }
+
+    /// Adds the entries of `other` whose keys are not present yet. Existing
+    /// entries, which may have been edited by hand, are kept as they are.
+    pub fn merge_missing(&mut self, other: ArkData) {
+        for (key, value) in other.creatures {
+            self.creatures.entry(key).or_insert(value);
+        }
+        for (key, value) in other.items {
+            self.items.entry(key).or_insert(value);
+        }
+        for (key, value) in other.engrams {
+            self.engrams.entry(key).or_insert(value);
+        }
+        for (key, value) in other.beacons {
+            self.beacons.entry(key).or_insert(value);
+        }
+        for (key, value) in other.colors {
+            self.colors.entry(key).or_insert(value);
+        }
+        for (key, value) in other.icons {
+            self.icons.entry(key).or_insert(value);
+        }
+        for (key, value) in other.mods {
+            self.mods.entry(key).or_insert(value);
+        }
+    }
+}
+
 impl Default for ArkData {
     fn default() -> Self {
         Self {
@@ -72,8 +207,80 @@ impl Default for ArkData {
             beacons: HashMap::new(),
             colors: HashMap::new(),
             icons: HashMap::new(),
+            mods: HashMap::new(),
             version: "1.0.0".to_string(),
             last_updated: chrono::Utc::now().timestamp(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::mods::ModMap;
+    use crate::test_support;
+
+    #[test]
+    fn merging_adds_missing_entries_of_every_collection() {
+        let mut existing = ArkData::default();
+        let mut edited = test_support::item("Stone");
+        edited.name = "Edited Stone".to_string();
+        existing.items.insert("Stone".to_string(), edited);
+
+        let mut scraped = ArkData::default();
+        for name in ["Stone", "Wood"] {
+            scraped
+                .items
+                .insert(name.to_string(), test_support::item(name));
+        }
+        scraped.icons.insert(
+            "Stone".to_string(),
+            Icon {
+                type_name: "icon".to_string(),
+                name: "Stone".to_string(),
+                path: "Items/Stone.png".to_string(),
+            },
+        );
+
+        existing.merge_missing(scraped);
+        assert_eq!(existing.items["Stone"].name, "Edited Stone");
+        assert!(existing.items.contains_key("Wood"));
+        assert!(existing.icons.contains_key("Stone"));
+    }
+
+    #[test]
+    fn editions_come_from_markers_then_mods_then_config() {
+        assert_eq!(Edition::mentioned_in("ASA only"), [Edition::Asa]);
+        assert_eq!(
+            Edition::mentioned_in("Survival Evolved / Survival Ascended"),
+            [Edition::Ase, Edition::Asa]
+        );
+        // Whole words only, so "base" is not ASE
+        assert!(Edition::mentioned_in("Base Game").is_empty());
+
+        let mods = ModMap::default();
+        assert_eq!(mods.editions("ASE", "astraeos", None), [Edition::Ase]);
+        assert_eq!(mods.editions("Astraeos", "astraeos", None), [Edition::Asa]);
+        assert_eq!(
+            mods.editions("The Island", "ark", Some(Edition::Ase)),
+            [Edition::Ase]
+        );
+        assert!(mods.editions("The Island", "ark", None).is_empty());
+
+        let mut data = ArkData::default();
+        for (name, editions) in [
+            ("Stone", Vec::new()),
+            ("Wood", vec![Edition::Ase]),
+            ("Gem", vec![Edition::Asa]),
+        ] {
+            let mut item = test_support::item(name);
+            item.editions = editions;
+            data.items.insert(name.to_string(), item);
+        }
+
+        data.retain_edition(Edition::Asa);
+        let mut kept: Vec<_> = data.items.keys().cloned().collect();
+        kept.sort();
+        assert_eq!(kept, ["Gem", "Stone"]);
+    }
+}
@@ -0,0 +1,228 @@
+//! Blueprint references as the game and the wiki write them.
+//!
+//! The same creature can be given as `Blueprint'/Game/X/Dodo.Dodo'`, with
+//! the wrapper quoted for admin commands, as the bare object path
+//! `/Game/X/Dodo.Dodo`, or as the generated class `/Game/X/Dodo.Dodo_C`.
+//! [`Blueprint`] accepts all of them and renders each on request.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Wrappers a blueprint path may be pasted in.
+const WRAPPERS: &[&str] = &["Blueprint'", "BlueprintGeneratedClass'"];
+
+/// A parsed blueprint reference. Stored and serialized in the wrapped
+/// `Blueprint'/Game/...'` form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Blueprint {
+    /// Package path without the object name, e.g.
+    /// `/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP`.
+    package: String,
+}
+
+impl Blueprint {
+    /// Parses any form the game accepts, with or without the `Blueprint'...'`
+    /// wrapper, surrounding double quotes or the `_C` class suffix.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let text = text
+            .strip_prefix('"')
+            .and_then(|t| t.strip_suffix('"'))
+            .unwrap_or(text);
+
+        let path = match WRAPPERS.iter().find_map(|w| text.strip_prefix(w)) {
+            Some(rest) => rest
+                .strip_suffix('\'')
+                .ok_or_else(|| format!("Blueprint '{}' is missing its closing quote", text))?,
+            None => text,
+        };
+
+        if !path.starts_with('/') {
+            return Err(format!("Blueprint path '{}' must start with '/'", path));
+        }
+
+        let (package, object) = match path.split_once('.') {
+            Some((package, object)) => (package, Some(object)),
+            None => (path, None),
+        };
+
+        let (folder, asset) = package
+            .rsplit_once('/')
+            .ok_or_else(|| format!("Blueprint path '{}' has no asset name", path))?;
+        if folder.is_empty() || asset.is_empty() {
+            return Err(format!(
+                "Blueprint path '{}' has no folder or asset name",
+                path
+            ));
+        }
+
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if !package
+            .split('/')
+            .skip(1)
+            .all(|s| !s.is_empty() && s.chars().all(valid))
+        {
+            return Err(format!("Blueprint path '{}' has an invalid segment", path));
+        }
+
+        if let Some(object) = object {
+            let object = object.strip_suffix("_C").unwrap_or(object);
+            if object != asset {
+                return Err(format!(
+                    "Blueprint object '{}' does not match asset '{}'",
+                    object, asset
+                ));
+            }
+        }
+
+        Ok(Self {
+            package: package.to_string(),
+        })
+    }
+
+    /// The package path, e.g. `/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP`.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The asset name, e.g. `Dodo_Character_BP`.
+    pub fn asset(&self) -> &str {
+        self.package.rsplit('/').next().unwrap_or(&self.package)
+    }
+
+    /// The folder holding the asset, e.g. `Dodo`.
+    pub fn folder(&self) -> &str {
+        self.package.rsplit('/').nth(1).unwrap_or_default()
+    }
+
+    /// The generated class, e.g. `Dodo_Character_BP_C`.
+    pub fn class_name(&self) -> String {
+        format!("{}_C", self.asset())
+    }
+
+    /// `/Game/.../Dodo_Character_BP.Dodo_Character_BP`
+    pub fn object_path(&self) -> String {
+        format!("{}.{}", self.package, self.asset())
+    }
+
+    /// `Blueprint'/Game/.../Dodo_Character_BP.Dodo_Character_BP'`, the form
+    /// entries store.
+    pub fn wrapped(&self) -> String {
+        format!("Blueprint'{}'", self.object_path())
+    }
+
+    /// The wrapped form in double quotes, as admin commands take it.
+    pub fn quoted(&self) -> String {
+        format!("\"{}\"", self.wrapped())
+    }
+
+    /// `/Game/.../Dodo_Character_BP.Dodo_Character_BP_C`
+    pub fn class_path(&self) -> String {
+        format!("{}_C", self.object_path())
+    }
+
+    /// Every rendering at once, for the frontend.
+    pub fn forms(&self) -> BlueprintForms {
+        BlueprintForms {
+            package: self.package.clone(),
+            asset: self.asset().to_string(),
+            class_name: self.class_name(),
+            object_path: self.object_path(),
+            wrapped: self.wrapped(),
+            quoted: self.quoted(),
+            class_path: self.class_path(),
+        }
+    }
+}
+
+impl fmt::Display for Blueprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.wrapped())
+    }
+}
+
+impl FromStr for Blueprint {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl TryFrom<String> for Blueprint {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text)
+    }
+}
+
+impl From<Blueprint> for String {
+    fn from(blueprint: Blueprint) -> Self {
+        blueprint.wrapped()
+    }
+}
+
+/// The parts and renderings of a [`Blueprint`].
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BlueprintForms {
+    pub package: String,
+    pub asset: String,
+    pub class_name: String,
+    pub object_path: String,
+    pub wrapped: String,
+    pub quoted: String,
+    pub class_path: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::common::extract_blueprint;
+
+    #[test]
+    fn blueprints_parse_from_every_form_and_render_each() {
+        let wrapped = "Blueprint'/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP'";
+        for input in [
+            wrapped,
+            "\"Blueprint'/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP'\"",
+            "  /Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP ",
+            "/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP_C",
+            "BlueprintGeneratedClass'/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP_C'",
+            "/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP",
+        ] {
+            let blueprint = Blueprint::parse(input).unwrap();
+            assert_eq!(blueprint.wrapped(), wrapped, "{}", input);
+        }
+
+        let blueprint: Blueprint = wrapped.parse().unwrap();
+        assert_eq!(blueprint.asset(), "Dodo_Character_BP");
+        assert_eq!(blueprint.folder(), "Dodo");
+        assert_eq!(blueprint.class_name(), "Dodo_Character_BP_C");
+        assert_eq!(
+            blueprint.class_path(),
+            "/Game/PrimalEarth/Dinos/Dodo/Dodo_Character_BP.Dodo_Character_BP_C"
+        );
+        assert_eq!(blueprint.quoted(), format!("\"{}\"", wrapped));
+        assert_eq!(
+            serde_json::to_value(&blueprint).unwrap(),
+            serde_json::json!(wrapped)
+        );
+
+        for invalid in [
+            "Unknown",
+            "Blueprint'/Game/Dodo/Dodo.Dodo",
+            "/Game/Dodo/Dodo.Raptor",
+            "Dodo_Character_BP_C",
+        ] {
+            assert!(Blueprint::parse(invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(
+            extract_blueprint(&format!("cheat summon {} 1", wrapped)).as_deref(),
+            Some(wrapped)
+        );
+    }
+}
@@ -0,0 +1,11 @@
+use crate::schedule::ScheduleSettings;
+use crate::scrapers::config::ScraperConfig;
+use serde::{Deserialize, Serialize};
+
+/// Application settings persisted next to the data file.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub scraper: ScraperConfig,
+    pub schedule: ScheduleSettings,
+}
@@ -0,0 +1,178 @@
+use crate::types::blueprint::Blueprint;
+use crate::types::*;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// An entry that failed validation.
+#[derive(Debug, Serialize, Clone)]
+pub struct ValidationIssue {
+    pub category: String,
+    pub key: String,
+    pub error: String,
+}
+
+/// Checks one entry of `category` ("creatures", "items", ...).
+pub fn validate(category: &str, data: Value) -> Result<bool, String> {
+    match category {
+        "creatures" => validate_creature(data),
+        "items" => validate_item(data),
+        "engrams" => validate_engram(data),
+        "beacons" => validate_beacon(data),
+        "colors" => validate_color(data),
+        _ => Err("Invalid category".to_string()),
+    }
+}
+
+fn validate_creature(data: Value) -> Result<bool, String> {
+    let creature: Creature =
+        serde_json::from_value(data).map_err(|e| format!("Invalid creature data: {}", e))?;
+
+    validate_blueprint(&creature.blueprint)?;
+
+    // Validate entity ID format
+    if !creature.entity_id.ends_with("_C") {
+        return Err("Invalid entity ID format".to_string());
+    }
+
+    Ok(true)
+}
+
+fn validate_item(data: Value) -> Result<bool, String> {
+    let item: Item =
+        serde_json::from_value(data).map_err(|e| format!("Invalid item data: {}", e))?;
+
+    // Validate class name format
+    if !item.class_name.ends_with("_C") {
+        return Err("Invalid class name format".to_string());
+    }
+
+    validate_blueprint(&item.blueprint)?;
+
+    Ok(true)
+}
+
+fn validate_engram(data: Value) -> Result<bool, String> {
+    let engram: Engram =
+        serde_json::from_value(data).map_err(|e| format!("Invalid engram data: {}", e))?;
+
+    validate_blueprint(&engram.blueprint)?;
+
+    Ok(true)
+}
+
+fn validate_beacon(data: Value) -> Result<bool, String> {
+    let beacon: Beacon =
+        serde_json::from_value(data).map_err(|e| format!("Invalid beacon data: {}", e))?;
+
+    // Validate class name format
+    if !beacon.class_name.ends_with("_C") {
+        return Err("Invalid class name format".to_string());
+    }
+
+    Ok(true)
+}
+
+/// Entries store blueprints in the wrapped `Blueprint'/Game/...'` form.
+fn validate_blueprint(text: &str) -> Result<(), String> {
+    let blueprint =
+        Blueprint::parse(text).map_err(|e| format!("Invalid blueprint format: {}", e))?;
+
+    if blueprint.wrapped() != text {
+        return Err(format!(
+            "Blueprint is not normalised; expected {}",
+            blueprint.wrapped()
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_color(data: Value) -> Result<bool, String> {
+    let color: Color =
+        serde_json::from_value(data).map_err(|e| format!("Invalid color data: {}", e))?;
+
+    // Validate hex code format
+    let hex_regex = Regex::new(r"^#[0-9A-Fa-f]{6}$").unwrap();
+    if !hex_regex.is_match(&color.hex_code) {
+        return Err("Invalid hex code format".to_string());
+    }
+
+    Ok(true)
+}
+
+/// Checks every entry in `data`, in key order, and that every mod an entry
+/// references is registered. Icons have no rules to check.
+pub fn validate_data(data: &ArkData) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    collect_issues(&mut issues, "creatures", &data.creatures);
+    collect_issues(&mut issues, "items", &data.items);
+    collect_issues(&mut issues, "engrams", &data.engrams);
+    collect_issues(&mut issues, "beacons", &data.beacons);
+    collect_issues(&mut issues, "colors", &data.colors);
+
+    let mods = &data.mods;
+    collect_mod_issues(&mut issues, "creatures", mods, &data.creatures, |c| {
+        &c.mod_id
+    });
+    collect_mod_issues(&mut issues, "items", mods, &data.items, |i| &i.mod_id);
+    collect_mod_issues(&mut issues, "engrams", mods, &data.engrams, |e| &e.mod_id);
+    collect_mod_issues(&mut issues, "beacons", mods, &data.beacons, |b| &b.mod_id);
+
+    let sorted: BTreeMap<_, _> = mods.iter().collect();
+    for (key, registered) in sorted {
+        if *key != registered.id {
+            issues.push(ValidationIssue {
+                category: "mods".to_string(),
+                key: key.clone(),
+                error: format!(
+                    "Mod is stored under '{}' but has ID '{}'",
+                    key, registered.id
+                ),
+            });
+        }
+    }
+    issues
+}
+
+fn collect_mod_issues<T>(
+    issues: &mut Vec<ValidationIssue>,
+    category: &str,
+    mods: &HashMap<String, Mod>,
+    entries: &HashMap<String, T>,
+    mod_id: impl Fn(&T) -> &String,
+) {
+    let sorted: BTreeMap<_, _> = entries.iter().collect();
+    for (key, entry) in sorted {
+        let mod_id = mod_id(entry);
+        if !mods.contains_key(mod_id) {
+            issues.push(ValidationIssue {
+                category: category.to_string(),
+                key: key.clone(),
+                error: format!("Unknown mod '{}'", mod_id),
+            });
+        }
+    }
+}
+
+fn collect_issues<T: Serialize>(
+    issues: &mut Vec<ValidationIssue>,
+    category: &str,
+    entries: &HashMap<String, T>,
+) {
+    let sorted: BTreeMap<_, _> = entries.iter().collect();
+    for (key, entry) in sorted {
+        let result = serde_json::to_value(entry)
+            .map_err(|e| format!("Failed to serialize entry: {}", e))
+            .and_then(|value| validate(category, value));
+
+        if let Err(error) = result {
+            issues.push(ValidationIssue {
+                category: category.to_string(),
+                key: key.clone(),
+                error,
+            });
+        }
+    }
+}
@@ -5,6 +5,7 @@ import { Card, CardBody, Spinner } from "@nextui-org/react";
 import useArkStore from "@/store/arkStore";
 import Header from "@/components/Header";
 import ScrapingProgress from "@/components/ScrapingProgress";
+import ScheduledChanges from "@/components/ScheduledChanges";
 import DataTabs from "@/components/DataTabs";
 
 export default function Home() {
@@ -28,6 +29,8 @@ export default function Home() {
     <div className="flex flex-col w-full min-h-screen p-4 gap-4">
       <Header />
 
+      <ScheduledChanges />
+
       {scraping && (
         <Card>
           <CardBody>
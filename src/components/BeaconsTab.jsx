@@ -11,11 +11,11 @@ const BeaconsTab = () => {
   const [search, setSearch] = useState("");
 
   const handleSubmit = useCallback(
-    (key, data) => {
-      addEntry("beacons", key, {
+    (data) => {
+      return addEntry("beacons", {
         type_name: "beacon",
         name: data.name,
-        mod_name: "Ark",
+        mod_id: "ark",
         class_name: data.class_name,
       });
     },
@@ -37,7 +37,7 @@ const BeaconsTab = () => {
         renderCell: (item) => (
           <div className="flex flex-col">
             <span className="font-bold">{item.name}</span>
-            <span className="text-sm text-gray-500">Mod: {item.mod_name}</span>
+            <span className="text-sm text-gray-500">Mod: {arkData.mods?.[item.mod_id]?.name ?? item.mod_id}</span>
           </div>
         ),
       },
@@ -65,7 +65,7 @@ const BeaconsTab = () => {
         ),
       },
     ],
-    [handleDelete]
+    [arkData.mods, handleDelete]
   );
 
   const filteredData = useMemo(() => {
@@ -22,14 +22,14 @@ const ColorsTab = () => {
       color.name.toLowerCase().includes(search.toLowerCase())
   );
 
-  const handleSubmit = (key, data) => {
+  const handleSubmit = (data) => {
     // Validate hex code format
     const hexCode = data.hex_code.startsWith("#")
       ? data.hex_code
       : `#${data.hex_code}`;
     const colorId = parseInt(data.color_id);
 
-    addEntry("colors", key, {
+    return addEntry("colors", {
       type_name: "color",
       name: data.name,
       color_id: colorId,
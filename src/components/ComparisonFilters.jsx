@@ -17,10 +17,10 @@ const ComparisonFilters = ({ category, onFilterChange }) => {
   );
 
   const fieldOptions = {
-    creatures: ["name", "entity_id", "blueprint", "mod_name"],
-    items: ["name", "class_name", "blueprint", "type_name", "mod_name"],
-    engrams: ["name", "blueprint", "mod_name"],
-    beacons: ["name", "class_name", "mod_name"],
+    creatures: ["name", "entity_id", "blueprint", "mod_id"],
+    items: ["name", "class_name", "blueprint", "type_name", "mod_id"],
+    engrams: ["name", "blueprint", "mod_id"],
+    beacons: ["name", "class_name", "mod_id"],
     colors: ["name", "color_id", "hex_code"],
     icons: ["name", "path"],
   };
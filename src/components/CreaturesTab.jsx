@@ -11,8 +11,8 @@ const CreaturesTab = () => {
   const [search, setSearch] = useState("");
 
   const handleSubmit = useCallback(
-    (key, data) => {
-      addEntry("creatures", key, {
+    (data) => {
+      return addEntry("creatures", {
         ...data,
         type_name: "creature",
       });
@@ -35,7 +35,7 @@ const CreaturesTab = () => {
         renderCell: (item) => (
           <div className="flex flex-col">
             <span className="font-bold">{item.name}</span>
-            <span className="text-sm text-gray-500">Mod: {item.mod_name}</span>
+            <span className="text-sm text-gray-500">Mod: {arkData.mods?.[item.mod_id]?.name ?? item.mod_id}</span>
           </div>
         ),
       },
@@ -72,7 +72,7 @@ const CreaturesTab = () => {
         ),
       },
     ],
-    [handleDelete]
+    [arkData.mods, handleDelete]
   );
 
   const filteredData = useMemo(() => {
@@ -93,7 +93,7 @@ const CreaturesTab = () => {
       <CollapsibleCard title="Add New Creature">
         <DataEntryForm
           category="Creature"
-          fields={["name", "mod_name", "entity_id", "blueprint"]}
+          fields={["name", "mod_id", "entity_id", "blueprint"]}
           onSubmit={handleSubmit}
         />
       </CollapsibleCard>
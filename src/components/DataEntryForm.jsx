@@ -1,5 +1,6 @@
 // src/components/DataEntryForm.jsx
 import { useState } from "react";
+import { invoke } from "@tauri-apps/api/tauri";
 import { Input, Button, Select, SelectItem } from "@nextui-org/react";
 import { Plus } from "lucide-react";
 
@@ -18,8 +19,9 @@ const validateField = (field, value) => {
       }
       break;
     case "blueprint":
-      if (value && !value.includes("Blueprint")) {
-        return 'Blueprint must contain "Blueprint" keyword';
+      // Checked and normalised by the backend on submit
+      if (!value.trim()) {
+        return "This field is required";
       }
       break;
     case "class_name":
@@ -47,7 +49,7 @@ const DataEntryForm = ({ category, fields, onSubmit, types = [] }) => {
   const [formData, setFormData] = useState(initialFormData);
   const [errors, setErrors] = useState({});
 
-  const handleSubmit = (e) => {
+  const handleSubmit = async (e) => {
     e.preventDefault();
 
     // Validate all fields
@@ -64,8 +66,24 @@ const DataEntryForm = ({ category, fields, onSubmit, types = [] }) => {
       return;
     }
 
-    const key = formData.name.replace(/ /g, "_");
-    onSubmit(key, formData);
+    // Accept the path with or without the Blueprint'...' wrapper
+    let entry = formData;
+    if (fields.includes("blueprint")) {
+      try {
+        const { wrapped } = await invoke("parse_blueprint", {
+          text: formData.blueprint,
+        });
+        entry = { ...formData, blueprint: wrapped };
+      } catch (error) {
+        setErrors({ blueprint: String(error) });
+        return;
+      }
+    }
+
+    // Keep the input when the entry couldn't be added
+    if ((await onSubmit(entry)) === false) {
+      return;
+    }
     setFormData(initialFormData);
     setErrors({});
   };
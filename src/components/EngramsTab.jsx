@@ -11,11 +11,11 @@ const EngramsTab = () => {
   const [search, setSearch] = useState("");
 
   const handleSubmit = useCallback(
-    (key, data) => {
-      addEntry("engrams", key, {
+    (data) => {
+      return addEntry("engrams", {
         ...data,
         type_name: "engram",
-        mod_name: "Ark",
+        mod_id: "ark",
       });
     },
     [addEntry]
@@ -36,7 +36,7 @@ const EngramsTab = () => {
         renderCell: (item) => (
           <div className="flex flex-col">
             <span className="font-bold">{item.name}</span>
-            <span className="text-sm text-gray-500">Mod: {item.mod_name}</span>
+            <span className="text-sm text-gray-500">Mod: {arkData.mods?.[item.mod_id]?.name ?? item.mod_id}</span>
           </div>
         ),
       },
@@ -73,7 +73,7 @@ const EngramsTab = () => {
         ),
       },
     ],
-    [handleDelete]
+    [arkData.mods, handleDelete]
   );
 
   const filteredData = useMemo(() => {
@@ -94,7 +94,7 @@ const EngramsTab = () => {
       <CollapsibleCard title="Add New Engram">
         <DataEntryForm
           category="Engram"
-          fields={["name", "blueprint"]}
+          fields={["name", "class_name", "blueprint"]}
           onSubmit={handleSubmit}
         />
       </CollapsibleCard>
@@ -31,6 +31,9 @@ import { createBackup, exportData, importData } from "@/utils/dataUtils";
 import useArkStore from "@/store/arkStore";
 import DataComparison from "./DataComparison";
 
+const formatEta = (secs) =>
+  secs >= 60 ? `${Math.round(secs / 60)}m` : `${Math.max(secs, 1)}s`;
+
 const Header = () => {
   const {
     startScraping,
@@ -229,7 +232,11 @@ const Header = () => {
                   {scraping
                     ? `${scrapingProgress.stage || "Updating"} (${Math.round(
                         scrapingProgress.progress
-                      )}%)`
+                      )}%${
+                        scrapingProgress.eta_secs != null
+                          ? `, ~${formatEta(scrapingProgress.eta_secs)} left`
+                          : ""
+                      })`
                     : "Update Data"}
                 </Button>
 
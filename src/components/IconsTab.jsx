@@ -12,8 +12,8 @@ const IconsTab = () => {
   const [search, setSearch] = useState("");
 
   const handleSubmit = useCallback(
-    (key, data) => {
-      addEntry("icons", key, {
+    (data) => {
+      return addEntry("icons", {
         type_name: "icon",
         name: data.name,
         path: data.path,
@@ -24,10 +24,10 @@ const ItemsTab = () => {
   const [search, setSearch] = useState("");
 
   const handleSubmit = useCallback(
-    (key, data) => {
-      addEntry("items", key, {
+    (data) => {
+      return addEntry("items", {
         ...data,
-        mod_name: "Ark",
+        mod_id: "ark",
       });
     },
     [addEntry]
@@ -48,7 +48,7 @@ const ItemsTab = () => {
         renderCell: (item) => (
           <div className="flex flex-col">
             <span className="font-bold">{item.name}</span>
-            <span className="text-sm text-gray-500">Mod: {item.mod_name}</span>
+            <span className="text-sm text-gray-500">Mod: {arkData.mods?.[item.mod_id]?.name ?? item.mod_id}</span>
             <span className="text-sm text-gray-500">
               Type: {item.type_name}
             </span>
@@ -88,7 +88,7 @@ const ItemsTab = () => {
         ),
       },
     ],
-    [handleDelete]
+    [arkData.mods, handleDelete]
   );
 
   const filteredData = useMemo(() => {
@@ -0,0 +1,73 @@
+// src/components/ScheduledChanges.jsx
+import { useEffect } from "react";
+import { Button, Card, CardBody } from "@nextui-org/react";
+import { CalendarClock } from "lucide-react";
+import useArkStore from "@/store/arkStore";
+
+// Changes found by a scheduled scrape, to apply or discard
+const ScheduledChanges = () => {
+  const {
+    scheduledChanges,
+    loadData,
+    initScheduleListener,
+    loadPendingChanges,
+    applyPendingChanges,
+    discardPendingChanges,
+    dismissScheduledChanges,
+  } = useArkStore();
+
+  // Also pick up changes left from before the app was started
+  useEffect(() => {
+    initScheduleListener();
+    loadPendingChanges();
+  }, [initScheduleListener, loadPendingChanges]);
+
+  if (!scheduledChanges) {
+    return null;
+  }
+
+  const { added, changed, removed, merged } = scheduledChanges;
+
+  const handleReload = async () => {
+    await loadData();
+    dismissScheduledChanges();
+  };
+
+  return (
+    <Card>
+      <CardBody className="flex flex-row flex-wrap items-center gap-2 text-sm">
+        <CalendarClock className="w-4 h-4" />
+        <span className="flex-1">
+          Scheduled scrape {merged ? "merged" : "found"} {added} new, {changed}{" "}
+          changed and {removed} removed entries
+        </span>
+        {merged ? (
+          <Button size="sm" variant="flat" onPress={handleReload}>
+            Reload data
+          </Button>
+        ) : (
+          <>
+            <Button
+              size="sm"
+              color="primary"
+              variant="flat"
+              onPress={applyPendingChanges}
+            >
+              Apply
+            </Button>
+            <Button
+              size="sm"
+              color="danger"
+              variant="light"
+              onPress={discardPendingChanges}
+            >
+              Discard
+            </Button>
+          </>
+        )}
+      </CardBody>
+    </Card>
+  );
+};
+
+export default ScheduledChanges;
@@ -1,7 +1,7 @@
 // src/components/ScrapingProgress.jsx
 import { Progress, Card, CardBody } from "@nextui-org/react";
 
-const ScrapingProgress = ({ stage, progress, message }) => {
+const ScrapingProgress = ({ stage, progress, message, stages = [] }) => {
   const stageColors = {
     creatures: "primary",
     items: "secondary",
@@ -23,6 +23,24 @@ const ScrapingProgress = ({ stage, progress, message }) => {
             className="w-full"
           />
           <span className="text-sm text-gray-500">{message}</span>
+          {stages.length > 0 && (
+            <div className="flex flex-col gap-1 mt-2">
+              {stages.map((s) => (
+                <div key={s.stage} className="flex items-center gap-2">
+                  <span className="text-xs capitalize w-20">{s.stage}</span>
+                  <Progress
+                    size="sm"
+                    value={s.progress}
+                    color={stageColors[s.stage] || "default"}
+                    className="flex-1"
+                  />
+                  <span className="text-xs w-10 text-right">
+                    {Math.round(s.progress)}%
+                  </span>
+                </div>
+              ))}
+            </div>
+          )}
         </div>
       </CardBody>
     </Card>
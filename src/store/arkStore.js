@@ -15,6 +15,7 @@ const useArkStore = create((set, get) => ({
     beacons: {},
     colors: {},
     icons: {},
+    mods: {},
     version: "1.0.0",
     last_updated: Date.now()
   },
@@ -30,6 +31,8 @@ const useArkStore = create((set, get) => ({
     progress: 0,
     message: '',
   },
+  lastScrapeReport: null,
+  scheduledChanges: null,
 
   // History management
   history: [],
@@ -66,6 +69,74 @@ const useArkStore = create((set, get) => ({
     set({ listenerInitialized: true });
   },
 
+  // Listens for changes found by scheduled background scrapes
+  initScheduleListener: async () => {
+    if (get().scheduleListenerInitialized) return;
+
+    await listen('scheduled-scrape-changes', (event) => {
+      set({ scheduledChanges: event.payload });
+    });
+
+    set({ scheduleListenerInitialized: true });
+  },
+
+  // Fetches the changes a scheduled scrape left for review, if any, and
+  // shows them the way the change event does
+  loadPendingChanges: async () => {
+    try {
+      const pending = await invoke('get_pending_changes');
+      if (pending) {
+        const collections = Object.values(pending.changes.collections);
+        const count = (field) =>
+          collections.reduce((sum, c) => sum + Object.keys(c[field]).length, 0);
+        set({
+          scheduledChanges: {
+            created_at: pending.created_at,
+            added: count('added'),
+            removed: count('removed'),
+            changed: count('changed'),
+            merged: false,
+          },
+        });
+      }
+      return pending;
+    } catch (error) {
+      set({ error: error.toString() });
+      return null;
+    }
+  },
+
+  // Hides the notice about changes a scheduled scrape already merged
+  dismissScheduledChanges: () => set({ scheduledChanges: null }),
+
+  applyPendingChanges: async () => {
+    // The merge is saved and loaded from disk, which would drop unsaved edits
+    if (get().unsavedChanges) {
+      set({ error: 'Save or discard your changes before applying scheduled changes' });
+      return false;
+    }
+
+    try {
+      const merged = await invoke('apply_pending_changes');
+      set({ arkData: merged, scheduledChanges: null, unsavedChanges: false });
+      return true;
+    } catch (error) {
+      set({ error: error.toString() });
+      return false;
+    }
+  },
+
+  discardPendingChanges: async () => {
+    try {
+      await invoke('discard_pending_changes');
+      set({ scheduledChanges: null });
+      return true;
+    } catch (error) {
+      set({ error: error.toString() });
+      return false;
+    }
+  },
+
   // Version management
   incrementMajorVersion: () => {
     set(state => {
@@ -211,9 +282,23 @@ const useArkStore = create((set, get) => ({
   },
 
   // Entry management
-  addEntry: (category, key, data) => {
+  addEntry: async (category, data) => {
     const { pushToHistory } = get();
-    
+
+    // Keyed the way scraped entries are, so a later scrape updates this one
+    let key;
+    // Entries reference mods by ID, so the mod has to be in the registry
+    let newMod = null;
+    try {
+      key = await invoke('entry_key', { category, data });
+      if (data.mod_id && !get().arkData.mods?.[data.mod_id]) {
+        newMod = await invoke('mod_registry_entry', { id: data.mod_id });
+      }
+    } catch (error) {
+      set({ error: error.toString() });
+      return false;
+    }
+
     set(state => ({
       arkData: {
         ...state.arkData,
@@ -221,10 +306,14 @@ const useArkStore = create((set, get) => ({
           ...state.arkData[category],
           [key]: data,
         },
+        mods: newMod
+          ? { ...state.arkData.mods, [newMod.id]: newMod }
+          : state.arkData.mods,
       },
     }));
 
     pushToHistory(`Add ${category} entry: ${key}`);
+    return true;
   },
 
   removeEntry: (category, key) => {
@@ -258,10 +347,11 @@ const useArkStore = create((set, get) => ({
       });
 
       await get().initScrapingListener();
-      const scrapedData = await invoke('start_scraping');
+      const { data: scrapedData, report } = await invoke('start_scraping');
       
       set({ 
         scraping: false,
+        lastScrapeReport: report,
         scrapingProgress: {
           stage: 'complete',
           progress: 100,
@@ -284,6 +374,89 @@ const useArkStore = create((set, get) => ({
     }
   },
 
+  // Re-scrapes only the sections and pages that failed in the last run and
+  // returns that run's data with the recovered entries added
+  retryFailedUnits: async () => {
+    try {
+      set({
+        scraping: true,
+        error: null,
+        scrapingProgress: {
+          stage: 'initializing',
+          progress: 0,
+          message: 'Retrying failed pages...'
+        }
+      });
+
+      await get().initScrapingListener();
+      const { data: scrapedData, report } = await invoke('retry_failed_units');
+
+      set({
+        scraping: false,
+        lastScrapeReport: report,
+        scrapingProgress: {
+          stage: 'complete',
+          progress: 100,
+          message: 'Retry complete'
+        }
+      });
+
+      return scrapedData;
+    } catch (error) {
+      set({
+        error: error.toString(),
+        scraping: false,
+        scrapingProgress: {
+          stage: 'error',
+          progress: 0,
+          message: error.toString()
+        }
+      });
+      return null;
+    }
+  },
+
+  // Runs a scrape and returns what it would change, without touching the data
+  previewScraping: async () => {
+    try {
+      set({
+        scraping: true,
+        error: null,
+        scrapingProgress: {
+          stage: 'initializing',
+          progress: 0,
+          message: 'Starting preview...'
+        }
+      });
+
+      await get().initScrapingListener();
+      const { changes, report } = await invoke('preview_scraping');
+
+      set({
+        scraping: false,
+        lastScrapeReport: report,
+        scrapingProgress: {
+          stage: 'complete',
+          progress: 100,
+          message: 'Preview complete'
+        }
+      });
+
+      return changes;
+    } catch (error) {
+      set({
+        error: error.toString(),
+        scraping: false,
+        scrapingProgress: {
+          stage: 'error',
+          progress: 0,
+          message: error.toString()
+        }
+      });
+      return null;
+    }
+  },
+
   // Comparison operations
   startComparison: (newData) => {
     const { arkData } = get();
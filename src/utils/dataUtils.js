@@ -3,8 +3,13 @@ import { invoke } from '@tauri-apps/api/tauri';
 import { save, open } from '@tauri-apps/api/dialog';
 import { writeTextFile, readTextFile } from '@tauri-apps/api/fs';
 
-export const exportData = async (data) => {
+// Pass 'ase' or 'asa' as edition to leave out entries of the other game
+export const exportData = async (data, edition = null) => {
   try {
+    if (edition) {
+      data = await invoke('filter_by_edition', { data, edition });
+    }
+
     const filePath = await save({
       filters: [{
         name: 'JSON',